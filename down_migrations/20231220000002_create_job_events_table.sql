@@ -0,0 +1,11 @@
+-- Rollback: Drop job_events table and jobs.attempts column
+-- This reverses migration: 20231220000002_create_job_events_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_job_events_job_id;
+
+-- Drop the job_events table
+DROP TABLE IF EXISTS job_events;
+
+-- Drop the attempts column
+ALTER TABLE jobs DROP COLUMN IF EXISTS attempts;
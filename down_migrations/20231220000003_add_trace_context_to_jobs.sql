@@ -0,0 +1,4 @@
+-- Rollback: Drop jobs.trace_context column
+-- This reverses migration: 20231220000003_add_trace_context_to_jobs
+
+ALTER TABLE jobs DROP COLUMN IF EXISTS trace_context;
@@ -0,0 +1,8 @@
+-- Rollback: Drop jobs.request_id column and its index
+-- This reverses migration: 20231220000004_add_request_id_to_jobs
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_request_id;
+
+-- Drop the request_id column
+ALTER TABLE jobs DROP COLUMN IF EXISTS request_id;
@@ -0,0 +1,4 @@
+-- Rollback: Drop api_keys table
+-- This reverses migration: 20231220000005_create_api_keys_table
+
+DROP TABLE IF EXISTS api_keys;
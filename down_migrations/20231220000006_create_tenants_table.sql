@@ -0,0 +1,12 @@
+-- Rollback: Drop tenant columns and tenants table
+-- This reverses migration: 20231220000006_create_tenants_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_tenant_status_created;
+
+-- Drop the tenant_id columns
+ALTER TABLE jobs DROP COLUMN IF EXISTS tenant_id;
+ALTER TABLE api_keys DROP COLUMN IF EXISTS tenant_id;
+
+-- Drop the tenants table
+DROP TABLE IF EXISTS tenants;
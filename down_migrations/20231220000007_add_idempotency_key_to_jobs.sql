@@ -0,0 +1,8 @@
+-- Rollback: Drop jobs.idempotency_key column and its unique index
+-- This reverses migration: 20231220000007_add_idempotency_key_to_jobs
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_idempotency_key;
+
+-- Drop the idempotency_key column
+ALTER TABLE jobs DROP COLUMN IF EXISTS idempotency_key;
@@ -0,0 +1,11 @@
+-- Rollback: Drop jobs unique key columns and their unique index
+-- This reverses migration: 20231220000008_add_unique_key_to_jobs
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_unique_lock;
+
+-- Drop the unique key columns
+ALTER TABLE jobs DROP COLUMN IF EXISTS unique_lock;
+ALTER TABLE jobs DROP COLUMN IF EXISTS unique_until;
+ALTER TABLE jobs DROP COLUMN IF EXISTS unique_scope;
+ALTER TABLE jobs DROP COLUMN IF EXISTS unique_key;
@@ -0,0 +1,5 @@
+-- Rollback: Drop jobs.payload column
+-- This reverses migration: 20231220000009_add_payload_to_jobs
+
+-- Drop the payload column
+ALTER TABLE jobs DROP COLUMN IF EXISTS payload;
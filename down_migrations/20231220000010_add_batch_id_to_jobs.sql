@@ -0,0 +1,8 @@
+-- Rollback: Drop jobs.batch_id column and its index
+-- This reverses migration: 20231220000010_add_batch_id_to_jobs
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_batch_id;
+
+-- Drop the batch_id column
+ALTER TABLE jobs DROP COLUMN IF EXISTS batch_id;
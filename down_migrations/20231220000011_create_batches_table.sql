@@ -0,0 +1,8 @@
+-- Rollback: Drop batches table
+-- This reverses migration: 20231220000011_create_batches_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_batches_tenant_created;
+
+-- Drop the batches table
+DROP TABLE IF EXISTS batches;
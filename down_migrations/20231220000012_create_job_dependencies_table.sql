@@ -0,0 +1,20 @@
+-- Rollback: Drop job_dependencies table and the blocked/cancelled statuses
+-- This reverses migration: 20231220000012_create_job_dependencies_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_job_dependencies_depends_on;
+
+-- Drop the job_dependencies table
+DROP TABLE IF EXISTS job_dependencies;
+
+-- Drop the on_parent_failure column
+ALTER TABLE jobs DROP COLUMN IF EXISTS on_parent_failure;
+
+-- Statuses unknown to the previous schema: blocked jobs become runnable, cancelled ones failed
+UPDATE jobs SET status = 'new' WHERE status = 'blocked';
+UPDATE jobs SET status = 'failed' WHERE status = 'cancelled';
+
+-- Restore the original status check
+ALTER TABLE jobs DROP CONSTRAINT IF EXISTS jobs_status_check;
+ALTER TABLE jobs ADD CONSTRAINT jobs_status_check
+    CHECK (status IN ('new', 'processing', 'success', 'failed'));
@@ -0,0 +1,18 @@
+-- Rollback: Drop workflows table and job retry columns
+-- This reverses migration: 20231220000013_create_workflows_table
+
+-- Drop the step index
+DROP INDEX IF EXISTS idx_jobs_workflow_step;
+
+-- Drop the retry and workflow columns
+ALTER TABLE jobs DROP COLUMN IF EXISTS run_at;
+ALTER TABLE jobs DROP COLUMN IF EXISTS retry_backoff_secs;
+ALTER TABLE jobs DROP COLUMN IF EXISTS max_attempts;
+ALTER TABLE jobs DROP COLUMN IF EXISTS workflow_step;
+ALTER TABLE jobs DROP COLUMN IF EXISTS workflow_id;
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_workflows_tenant_created;
+
+-- Drop the workflows table
+DROP TABLE IF EXISTS workflows;
@@ -0,0 +1,15 @@
+-- Rollback: Drop webhook_deliveries table and callback columns
+-- This reverses migration: 20231220000014_create_webhook_deliveries_table
+
+-- Drop the indexes
+DROP INDEX IF EXISTS idx_webhook_deliveries_job_id;
+DROP INDEX IF EXISTS idx_webhook_deliveries_due;
+
+-- Drop the webhook_deliveries table
+DROP TABLE IF EXISTS webhook_deliveries;
+
+-- Drop the callback columns
+ALTER TABLE jobs DROP COLUMN IF EXISTS result;
+ALTER TABLE jobs DROP COLUMN IF EXISTS api_key_id;
+ALTER TABLE jobs DROP COLUMN IF EXISTS callback_url;
+ALTER TABLE api_keys DROP COLUMN IF EXISTS webhook_secret;
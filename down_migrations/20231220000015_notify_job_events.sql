@@ -0,0 +1,8 @@
+-- Rollback: Stop publishing job events
+-- This reverses migration: 20231220000015_notify_job_events
+
+-- Drop the trigger
+DROP TRIGGER IF EXISTS notify_job_events ON job_events;
+
+-- Drop the trigger function
+DROP FUNCTION IF EXISTS notify_job_events();
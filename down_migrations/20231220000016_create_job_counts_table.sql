@@ -0,0 +1,22 @@
+-- Rollback: Drop job counters and status timestamps
+-- This reverses migration: 20231220000016_create_job_counts_table
+
+-- Drop the triggers
+DROP TRIGGER IF EXISTS count_job_status_insert ON jobs;
+DROP TRIGGER IF EXISTS count_job_status_update ON jobs;
+DROP TRIGGER IF EXISTS count_job_status_delete ON jobs;
+DROP TRIGGER IF EXISTS stamp_job_status ON jobs;
+
+-- Drop the trigger functions
+DROP FUNCTION IF EXISTS count_job_status();
+DROP FUNCTION IF EXISTS stamp_job_status();
+
+-- Drop the counters table
+DROP TABLE IF EXISTS job_counts;
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_tenant_finished;
+
+-- Drop the columns
+ALTER TABLE jobs DROP COLUMN IF EXISTS finished_at;
+ALTER TABLE jobs DROP COLUMN IF EXISTS started_at;
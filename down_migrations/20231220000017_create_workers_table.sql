@@ -0,0 +1,11 @@
+-- Rollback: Drop workers table
+-- This reverses migration: 20231220000017_create_workers_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_locked_by;
+
+-- Drop the column
+ALTER TABLE jobs DROP COLUMN IF EXISTS locked_by;
+
+-- Drop the table
+DROP TABLE IF EXISTS workers;
@@ -0,0 +1,8 @@
+-- Rollback: Drop paused_queues table
+-- This reverses migration: 20231220000018_create_paused_queues_table
+
+-- Drop the index
+DROP INDEX IF EXISTS idx_jobs_new_tenant_name_created;
+
+-- Drop the table
+DROP TABLE IF EXISTS paused_queues;
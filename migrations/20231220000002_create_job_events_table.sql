@@ -0,0 +1,17 @@
+-- Track how many times a job has been picked up by a worker
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS attempts INT NOT NULL DEFAULT 0;
+
+-- Create job_events table recording every status transition
+CREATE TABLE IF NOT EXISTS job_events (
+    id BIGSERIAL PRIMARY KEY,
+    job_id INT NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    from_status VARCHAR(20),
+    to_status VARCHAR(20) NOT NULL,
+    worker_id INT,
+    attempt INT NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Create index for reading a job's history in order
+CREATE INDEX IF NOT EXISTS idx_job_events_job_id ON job_events(job_id, id);
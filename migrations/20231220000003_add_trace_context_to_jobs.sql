@@ -0,0 +1,2 @@
+-- Store the W3C trace context of the request that enqueued each job
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS trace_context JSONB;
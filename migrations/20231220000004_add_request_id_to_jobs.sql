@@ -0,0 +1,5 @@
+-- Store the X-Request-Id of the API call that created each job
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS request_id VARCHAR(128);
+
+-- Create index for finding the jobs created by a given request
+CREATE INDEX IF NOT EXISTS idx_jobs_request_id ON jobs(request_id) WHERE request_id IS NOT NULL;
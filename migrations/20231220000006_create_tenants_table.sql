@@ -0,0 +1,16 @@
+-- Create tenants table holding per-tenant quotas (NULL = unlimited)
+CREATE TABLE IF NOT EXISTS tenants (
+    id VARCHAR(64) PRIMARY KEY,
+    max_concurrent_jobs INT CHECK (max_concurrent_jobs > 0),
+    max_queued_jobs INT CHECK (max_queued_jobs > 0),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Existing keys and jobs belong to the default tenant
+INSERT INTO tenants (id) VALUES ('default') ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES tenants(id);
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS tenant_id VARCHAR(64) NOT NULL DEFAULT 'default' REFERENCES tenants(id);
+
+-- Create index for per-tenant queue scans (quota counts, fair acquisition)
+CREATE INDEX IF NOT EXISTS idx_jobs_tenant_status_created ON jobs(tenant_id, status, created_at);
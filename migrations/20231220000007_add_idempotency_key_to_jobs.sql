@@ -0,0 +1,7 @@
+-- Client-supplied idempotency key, unique per tenant while set
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(255);
+
+-- Create partial unique index backing ON CONFLICT for idempotent submissions
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_idempotency_key
+    ON jobs(tenant_id, idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
@@ -0,0 +1,16 @@
+-- Singleton key: at most one job per tenant may hold it while its scope lasts
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS unique_key VARCHAR(255);
+
+-- How long the key is held: queued, queued_or_running or window
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS unique_scope VARCHAR(32);
+
+-- End of the hold for window-scoped keys
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS unique_until TIMESTAMP;
+
+-- Copy of unique_key while the hold is active; cleared when the scope ends
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS unique_lock VARCHAR(255);
+
+-- Create partial unique index enforcing one holder per key
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_unique_lock
+    ON jobs(tenant_id, unique_lock)
+    WHERE unique_lock IS NOT NULL;
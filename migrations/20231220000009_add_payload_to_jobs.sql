@@ -0,0 +1,2 @@
+-- Handler input supplied at submission
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS payload JSONB;
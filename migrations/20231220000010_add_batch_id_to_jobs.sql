@@ -0,0 +1,7 @@
+-- Groups the jobs created by one bulk upload
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS batch_id UUID;
+
+-- Create index for listing a batch
+CREATE INDEX IF NOT EXISTS idx_jobs_batch_id
+    ON jobs(tenant_id, batch_id)
+    WHERE batch_id IS NOT NULL;
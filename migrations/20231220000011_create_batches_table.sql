@@ -0,0 +1,21 @@
+-- Create batches table tracking named groups of jobs and their follow-ups
+CREATE TABLE IF NOT EXISTS batches (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'succeeded', 'failed')),
+    -- Follow-up job specs ({"name": ..., "payload": ...}) enqueued when the last member finishes
+    on_complete JSONB,
+    on_failure JSONB,
+    follow_up_job_id INT REFERENCES jobs(id) ON DELETE SET NULL,
+    -- Member counts, kept up to date as members finish so settling a batch does not rescan it
+    total INT NOT NULL DEFAULT 0,
+    pending INT NOT NULL DEFAULT 0,
+    succeeded INT NOT NULL DEFAULT 0,
+    failed INT NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP
+);
+
+-- Create index for listing a tenant's batches
+CREATE INDEX IF NOT EXISTS idx_batches_tenant_created ON batches(tenant_id, created_at);
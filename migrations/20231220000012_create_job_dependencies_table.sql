@@ -0,0 +1,19 @@
+-- Allow jobs to wait on parents and to be cancelled when a parent fails
+ALTER TABLE jobs DROP CONSTRAINT IF EXISTS jobs_status_check;
+ALTER TABLE jobs ADD CONSTRAINT jobs_status_check
+    CHECK (status IN ('new', 'processing', 'success', 'failed', 'blocked', 'cancelled'));
+
+-- What happens to a blocked job when a parent fails: cancel or run_anyway
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS on_parent_failure VARCHAR(16) NOT NULL DEFAULT 'cancel'
+    CHECK (on_parent_failure IN ('cancel', 'run_anyway'));
+
+-- Create job_dependencies table: job_id waits for depends_on_id
+CREATE TABLE IF NOT EXISTS job_dependencies (
+    job_id INT NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    depends_on_id INT NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    PRIMARY KEY (job_id, depends_on_id),
+    CHECK (job_id <> depends_on_id)
+);
+
+-- Create index for finding the children of a finished job
+CREATE INDEX IF NOT EXISTS idx_job_dependencies_depends_on ON job_dependencies(depends_on_id);
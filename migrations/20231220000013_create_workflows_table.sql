@@ -0,0 +1,24 @@
+-- Create workflows table tracking jobs expanded from a single workflow spec
+CREATE TABLE IF NOT EXISTS workflows (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(64) NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'succeeded', 'failed', 'cancelled')),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    finished_at TIMESTAMP
+);
+
+-- Create index for listing a tenant's workflows
+CREATE INDEX IF NOT EXISTS idx_workflows_tenant_created ON workflows(tenant_id, created_at);
+
+-- Link jobs to the workflow step they were expanded from
+ALTER TABLE jobs ADD COLUMN workflow_id UUID REFERENCES workflows(id) ON DELETE CASCADE;
+ALTER TABLE jobs ADD COLUMN workflow_step VARCHAR(64);
+
+-- Retry policy: a failed attempt below max_attempts goes back to 'new' after a backoff
+ALTER TABLE jobs ADD COLUMN max_attempts INT NOT NULL DEFAULT 1 CHECK (max_attempts >= 1);
+ALTER TABLE jobs ADD COLUMN retry_backoff_secs INT NOT NULL DEFAULT 0 CHECK (retry_backoff_secs >= 0);
+ALTER TABLE jobs ADD COLUMN run_at TIMESTAMP;
+
+-- Each step appears once per workflow
+CREATE UNIQUE INDEX IF NOT EXISTS idx_jobs_workflow_step ON jobs(workflow_id, workflow_step) WHERE workflow_id IS NOT NULL;
@@ -0,0 +1,31 @@
+-- Per-key secret used to sign completion webhooks (HMAC-SHA256); backfill existing keys
+ALTER TABLE api_keys ADD COLUMN webhook_secret CHAR(64);
+UPDATE api_keys
+SET webhook_secret = replace(gen_random_uuid()::text, '-', '') || replace(gen_random_uuid()::text, '-', '');
+ALTER TABLE api_keys ALTER COLUMN webhook_secret SET NOT NULL;
+
+-- Completion callback, the key that enqueued the job (signs the callback) and the handler's result
+ALTER TABLE jobs ADD COLUMN callback_url TEXT;
+ALTER TABLE jobs ADD COLUMN api_key_id INT REFERENCES api_keys(id) ON DELETE SET NULL;
+ALTER TABLE jobs ADD COLUMN result JSONB;
+
+-- Create webhook_deliveries table: one row per callback, retried by the dispatcher
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    id BIGSERIAL PRIMARY KEY,
+    job_id INT NOT NULL REFERENCES jobs(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_status_code INT,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP
+);
+
+-- Create index for the dispatcher's due-delivery scan
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries(next_attempt_at) WHERE status = 'pending';
+
+-- Create index for a job's delivery log
+CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_job_id ON webhook_deliveries(job_id, id);
@@ -0,0 +1,60 @@
+-- Publish every job status transition on the job_events channel (LISTEN/NOTIFY)
+-- so API replicas can stream events written by any worker process
+CREATE OR REPLACE FUNCTION notify_job_events()
+RETURNS TRIGGER AS $$
+DECLARE
+    event RECORD;
+    bulk BOOLEAN;
+BEGIN
+    -- The initial events of a multi-row insert (bulk uploads, batches, spawned jobs)
+    -- are published as one 'created' notification per tenant; listeners read them
+    -- from job_events instead of getting one notification per job
+    SELECT COUNT(*) > 1 INTO bulk FROM new_events;
+
+    IF bulk THEN
+        PERFORM pg_notify('job_events', json_build_object(
+            'kind', 'created',
+            'tenant_id', j.tenant_id,
+            'first_id', MIN(e.id),
+            'last_id', MAX(e.id),
+            'count', COUNT(*)
+        )::text)
+        FROM new_events e
+        JOIN jobs j ON j.id = e.job_id
+        WHERE e.from_status IS NULL
+        GROUP BY j.tenant_id;
+    END IF;
+
+    -- NOTIFY payloads are limited to 8000 bytes; long errors are truncated
+    FOR event IN
+        SELECT e.*, j.tenant_id, j.name
+        FROM new_events e
+        JOIN jobs j ON j.id = e.job_id
+        WHERE NOT bulk OR e.from_status IS NOT NULL
+        ORDER BY e.id
+    LOOP
+        PERFORM pg_notify('job_events', json_build_object(
+            'kind', 'event',
+            'id', event.id,
+            'job_id', event.job_id,
+            'tenant_id', event.tenant_id,
+            'name', event.name,
+            'from_status', event.from_status,
+            'to_status', event.to_status,
+            'worker_id', event.worker_id,
+            'attempt', event.attempt,
+            'error', left(event.error, 1000),
+            'created_at', event.created_at
+        )::text);
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Notifications are delivered when the inserting transaction commits
+CREATE TRIGGER notify_job_events
+    AFTER INSERT ON job_events
+    REFERENCING NEW TABLE AS new_events
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION notify_job_events();
@@ -0,0 +1,109 @@
+-- Track when each attempt started and when a job finished, for run time and throughput stats
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS started_at TIMESTAMP;
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS finished_at TIMESTAMP;
+
+-- Best guess for jobs that finished before this migration
+UPDATE jobs SET finished_at = updated_at WHERE status IN ('success', 'failed', 'cancelled');
+
+-- Create index for recent throughput and run time per tenant
+CREATE INDEX IF NOT EXISTS idx_jobs_tenant_finished ON jobs(tenant_id, finished_at) WHERE finished_at IS NOT NULL;
+
+-- Stamp started_at/finished_at on every status change, whichever code path makes it
+CREATE OR REPLACE FUNCTION stamp_job_status()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status = 'processing' THEN
+        NEW.started_at := NOW();
+        NEW.finished_at := NULL;
+    ELSIF NEW.status IN ('success', 'failed', 'cancelled') THEN
+        NEW.finished_at := NOW();
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER stamp_job_status
+    BEFORE INSERT OR UPDATE OF status ON jobs
+    FOR EACH ROW
+    EXECUTE FUNCTION stamp_job_status();
+
+-- Job counts by tenant, name and status, maintained by trigger so stats never scan jobs
+-- Each backend writes its own slot; concurrent transitions of the same kind of job
+-- update different rows instead of queueing on one counter. Readers sum the slots.
+CREATE TABLE IF NOT EXISTS job_counts (
+    tenant_id VARCHAR(64) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    slot SMALLINT NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (tenant_id, name, status, slot)
+);
+
+-- Apply each statement's changes at once: bumping the same counter row once per job
+-- of a bulk insert would add a row version per job for the next bump to walk past
+CREATE OR REPLACE FUNCTION count_job_status()
+RETURNS TRIGGER AS $$
+DECLARE
+    counter_slot SMALLINT := pg_backend_pid() % 16;
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        INSERT INTO job_counts (tenant_id, name, status, slot, count)
+        SELECT tenant_id, name, status, counter_slot, COUNT(*)
+        FROM new_jobs
+        GROUP BY tenant_id, name, status
+        ON CONFLICT (tenant_id, name, status, slot) DO UPDATE SET count = job_counts.count + EXCLUDED.count;
+    ELSIF TG_OP = 'DELETE' THEN
+        INSERT INTO job_counts (tenant_id, name, status, slot, count)
+        SELECT tenant_id, name, status, counter_slot, -COUNT(*)
+        FROM old_jobs
+        GROUP BY tenant_id, name, status
+        ON CONFLICT (tenant_id, name, status, slot) DO UPDATE SET count = job_counts.count + EXCLUDED.count;
+    ELSE
+        INSERT INTO job_counts (tenant_id, name, status, slot, count)
+        SELECT tenant_id, name, status, counter_slot, SUM(delta)
+        FROM (
+            SELECT o.tenant_id, o.name, o.status, -1 AS delta
+            FROM old_jobs o
+            JOIN new_jobs n ON n.id = o.id
+            WHERE n.status <> o.status
+            UNION ALL
+            SELECT n.tenant_id, n.name, n.status, 1 AS delta
+            FROM old_jobs o
+            JOIN new_jobs n ON n.id = o.id
+            WHERE n.status <> o.status
+        ) changes
+        GROUP BY tenant_id, name, status
+        ON CONFLICT (tenant_id, name, status, slot) DO UPDATE SET count = job_counts.count + EXCLUDED.count;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Block writes while backfilling so no change falls between the snapshot and the triggers
+LOCK TABLE jobs IN SHARE ROW EXCLUSIVE MODE;
+
+INSERT INTO job_counts (tenant_id, name, status, slot, count)
+SELECT tenant_id, name, status, 0, COUNT(*)
+FROM jobs
+GROUP BY tenant_id, name, status;
+
+-- Transition tables need one trigger per event and no column list
+CREATE TRIGGER count_job_status_insert
+    AFTER INSERT ON jobs
+    REFERENCING NEW TABLE AS new_jobs
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION count_job_status();
+
+CREATE TRIGGER count_job_status_update
+    AFTER UPDATE ON jobs
+    REFERENCING OLD TABLE AS old_jobs NEW TABLE AS new_jobs
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION count_job_status();
+
+CREATE TRIGGER count_job_status_delete
+    AFTER DELETE ON jobs
+    REFERENCING OLD TABLE AS old_jobs
+    FOR EACH STATEMENT
+    EXECUTE FUNCTION count_job_status();
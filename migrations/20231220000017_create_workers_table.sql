@@ -0,0 +1,23 @@
+-- Create workers table: one row per running worker loop, kept alive by heartbeats
+CREATE TABLE IF NOT EXISTS workers (
+    id UUID PRIMARY KEY,
+    host VARCHAR(255) NOT NULL,
+    pid INTEGER NOT NULL,
+    -- Worker number within its process, as recorded in job_events.worker_id
+    worker_id INTEGER NOT NULL,
+    -- Job names the worker pulls; empty means every queue
+    queues TEXT[] NOT NULL DEFAULT '{}',
+    permits_in_use INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_heartbeat_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Create index for removing stale workers
+CREATE INDEX IF NOT EXISTS idx_workers_last_heartbeat ON workers(last_heartbeat_at);
+
+-- Remember which worker acquired a job; not a foreign key so stale workers can be
+-- removed without touching their jobs
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS locked_by UUID;
+
+-- Create index for listing a worker's running jobs
+CREATE INDEX IF NOT EXISTS idx_jobs_locked_by ON jobs(locked_by) WHERE status = 'processing';
@@ -0,0 +1,14 @@
+-- Create paused_queues table: a tenant's job names workers must not acquire until resumed
+CREATE TABLE IF NOT EXISTS paused_queues (
+    tenant_id VARCHAR(64) NOT NULL REFERENCES tenants(id),
+    name VARCHAR(255) NOT NULL,
+    reason TEXT,
+    -- Name of the API key that paused the queue
+    paused_by VARCHAR(255) NOT NULL,
+    paused_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, name)
+);
+
+-- Create index for the oldest queued job of each tenant and name, so acquisition
+-- reads one row per name it may serve and never walks a paused name's backlog
+CREATE INDEX IF NOT EXISTS idx_jobs_new_tenant_name_created ON jobs(tenant_id, name, created_at) WHERE status = 'new';
@@ -0,0 +1,230 @@
+use actix_web::{
+    body::{EitherBody, MessageBody},
+    dev::{Payload, ServiceRequest, ServiceResponse},
+    error::ErrorUnauthorized,
+    http::{header, StatusCode},
+    middleware::Next,
+    web, Error, FromRequest, HttpMessage, HttpRequest, HttpResponse,
+};
+use std::future::{ready, Ready};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+use std::fmt;
+use tracing::{error, warn};
+
+use crate::api::validation::ErrorResponse;
+use crate::db::api_key_repository::ApiKeyRepository;
+use crate::db::models::ApiKeyRow;
+
+/// Header accepted as an alternative to `Authorization: Bearer <key>`
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Prefix of every generated key, so leaked keys are easy to recognize
+const KEY_PREFIX: &str = "jp_";
+
+/// Permission granted to an API key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Read jobs and their history
+    JobsRead,
+    /// Submit jobs
+    JobsWrite,
+    /// Scrape `/metrics`, e.g. for Prometheus
+    MetricsRead,
+    /// Everything, including operational endpoints
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::JobsRead => "jobs:read",
+            Scope::JobsWrite => "jobs:write",
+            Scope::MetricsRead => "metrics:read",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "jobs:read" => Some(Scope::JobsRead),
+            "jobs:write" => Some(Scope::JobsWrite),
+            "metrics:read" => Some(Scope::MetricsRead),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Authenticated API key attached to the request by the auth middleware
+#[derive(Debug, Clone)]
+pub struct Caller {
+    pub key_id: i32,
+    /// Tenant that owns every job this caller creates or reads
+    pub tenant_id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+}
+
+impl Caller {
+    /// `admin` implies every other scope
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.iter().any(|s| *s == scope || *s == Scope::Admin)
+    }
+}
+
+impl From<ApiKeyRow> for Caller {
+    fn from(row: ApiKeyRow) -> Self {
+        Self {
+            key_id: row.id,
+            tenant_id: row.tenant_id,
+            name: row.name,
+            // Unknown scope strings grant nothing
+            scopes: row.scopes.iter().filter_map(|s| Scope::parse(s)).collect(),
+        }
+    }
+}
+
+/// Extract the `Caller` stored by the auth middleware
+///
+/// Fails with 401 on routes that are not wrapped by one of the `require_*` middlewares.
+impl FromRequest for Caller {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<Caller>()
+                .cloned()
+                .ok_or_else(|| ErrorUnauthorized("Not authenticated")),
+        )
+    }
+}
+
+/// Generate a new random API key
+///
+/// # Returns
+/// `(key, prefix)` - the full key (shown once) and a short prefix safe to display
+pub fn generate_key() -> (String, String) {
+    let bytes: [u8; 24] = rand::thread_rng().gen();
+    let key = format!("{}{}", KEY_PREFIX, hex::encode(bytes));
+    let prefix = key[..KEY_PREFIX.len() + 6].to_string();
+    (key, prefix)
+}
+
+/// Generate a new random webhook signing secret (64 hex characters)
+pub fn generate_webhook_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+/// SHA-256 hex digest of an API key, as stored in `api_keys.key_hash`
+pub fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Read the presented key from `Authorization: Bearer` or `X-Api-Key`
+fn presented_key(req: &ServiceRequest) -> Option<String> {
+    let headers = req.headers();
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok()))
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+}
+
+/// Short-circuit the request with an error response
+fn reject<B>(req: ServiceRequest, status: StatusCode, message: &str) -> ServiceResponse<EitherBody<B>> {
+    let response = HttpResponse::build(status).json(ErrorResponse::new(
+        if status == StatusCode::FORBIDDEN { "Forbidden" } else { "Unauthorized" },
+        serde_json::json!({"message": message}),
+    ));
+    req.into_response(response).map_into_right_body()
+}
+
+/// Authenticate the request's API key and require `scope`
+///
+/// On success the `Caller` is stored in request extensions for handlers.
+async fn authorize<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+    scope: Scope,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    let Some(key) = presented_key(&req) else {
+        return Ok(reject(req, StatusCode::UNAUTHORIZED, "Missing API key"));
+    };
+
+    let Some(pool) = req.app_data::<web::Data<Pool<Postgres>>>().cloned() else {
+        error!("Database pool is not registered as app data");
+        return Ok(reject(req, StatusCode::INTERNAL_SERVER_ERROR, "Authentication unavailable"));
+    };
+
+    let api_key = match ApiKeyRepository::find_active_by_hash(pool.get_ref(), &hash_key(&key)).await {
+        Ok(Some(api_key)) => api_key,
+        Ok(None) => {
+            warn!("Rejected request with unknown or revoked API key");
+            return Ok(reject(req, StatusCode::UNAUTHORIZED, "Invalid API key"));
+        }
+        Err(e) => {
+            error!("Failed to look up API key: {:?}", e);
+            return Ok(reject(req, StatusCode::INTERNAL_SERVER_ERROR, "Authentication unavailable"));
+        }
+    };
+
+    let caller = Caller::from(api_key);
+    if !caller.has_scope(scope) {
+        warn!("API key {} ({}) lacks scope {}", caller.key_id, caller.name, scope);
+        return Ok(reject(
+            req,
+            StatusCode::FORBIDDEN,
+            &format!("API key lacks required scope: {}", scope),
+        ));
+    }
+
+    req.extensions_mut().insert(caller);
+
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
+
+/// Middleware requiring the `jobs:read` scope
+pub async fn require_jobs_read<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    authorize(req, next, Scope::JobsRead).await
+}
+
+/// Middleware requiring the `jobs:write` scope
+pub async fn require_jobs_write<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    authorize(req, next, Scope::JobsWrite).await
+}
+
+/// Middleware requiring the `metrics:read` scope
+pub async fn require_metrics_read<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    authorize(req, next, Scope::MetricsRead).await
+}
+
+/// Middleware requiring the `admin` scope
+pub async fn require_admin<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<EitherBody<B>>, Error> {
+    authorize(req, next, Scope::Admin).await
+}
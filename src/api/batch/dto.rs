@@ -0,0 +1,18 @@
+use serde::Serialize;
+use crate::api::job::dto::CreatedJob;
+use crate::db::models::{BatchCounts, BatchRow};
+
+/// Response for batch creation
+#[derive(Serialize)]
+pub struct BatchResponse {
+    pub message: String,
+    pub batch: BatchRow,
+    pub jobs: Vec<CreatedJob>,
+}
+
+/// Response for a batch's aggregate state
+#[derive(Serialize)]
+pub struct BatchStatusResponse {
+    pub batch: BatchRow,
+    pub counts: BatchCounts,
+}
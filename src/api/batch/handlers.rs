@@ -0,0 +1,42 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get, post,
+    middleware::from_fn,
+    web::{Data, Path, ServiceConfig, scope},
+};
+use actix_web_validator::Json;
+use uuid::Uuid;
+use crate::api::auth::{Caller, require_jobs_read, require_jobs_write};
+use super::models::CreateBatch;
+use super::service::BatchService;
+
+#[post("", wrap = "from_fn(require_jobs_write)")]
+async fn create_batch(
+    service: Data<BatchService>,
+    caller: Caller,
+    batch: Json<CreateBatch>,
+) -> impl Responder {
+    match service.create_batch(&caller, batch.into_inner()).await {
+        Ok(response) => HttpResponse::Created().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[get("/{id}", wrap = "from_fn(require_jobs_read)")]
+async fn get_batch(
+    service: Data<BatchService>,
+    caller: Caller,
+    path: Path<Uuid>,
+) -> impl Responder {
+    match service.get_batch(&caller, path.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn batch_config(config: &mut ServiceConfig) {
+    config.service(
+        scope("batches")
+            .service(create_batch)
+            .service(get_batch)
+    );
+}
@@ -0,0 +1,7 @@
+pub mod models;
+pub mod dto;
+pub mod handlers;
+pub mod service;
+
+// Re-export commonly used types
+pub use service::BatchService;
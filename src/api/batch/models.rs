@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::api::job::Job;
+
+/// Job enqueued automatically when a batch finishes
+#[derive(Deserialize, Serialize, Debug, Validate)]
+pub struct FollowUpJob {
+    #[validate(length(
+        min = 3,
+        max = 10,
+        message = "Name must be between 3 and 10 characters"
+    ))]
+    pub name: String,
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Batch model for creating a named group of jobs
+///
+/// Member jobs are validated individually by the service so errors can be
+/// reported per item.
+#[derive(Deserialize, Debug, Validate)]
+pub struct CreateBatch {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Batch name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+    #[validate(length(min = 1, message = "A batch needs at least one job"))]
+    pub jobs: Vec<Job>,
+    /// Enqueued when every member succeeded
+    #[validate(nested)]
+    pub on_complete: Option<FollowUpJob>,
+    /// Enqueued when the last member finished and any member failed
+    #[validate(nested)]
+    pub on_failure: Option<FollowUpJob>,
+}
@@ -0,0 +1,102 @@
+use sqlx::{Pool, Postgres};
+use tracing::{info, instrument, warn};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::api::auth::Caller;
+use crate::api::job::dto::{CreatedJob, JobError, Position};
+use crate::api::job::service::{enqueue_context, validation_messages, ServiceError};
+use crate::db::batch_repository::BatchRepository;
+use crate::metrics::metrics;
+use super::dto::{BatchResponse, BatchStatusResponse};
+use super::models::CreateBatch;
+
+/// Batch service containing business logic
+pub struct BatchService {
+    pool: Pool<Postgres>,
+    idempotency_retention_hours: i32,
+}
+
+impl BatchService {
+    /// Create a new BatchService instance
+    pub fn new(pool: Pool<Postgres>, idempotency_retention_hours: i32) -> Self {
+        Self { pool, idempotency_retention_hours }
+    }
+
+    /// Create a named batch of jobs
+    ///
+    /// # Business Logic
+    /// - Validates every member job; any invalid member rejects the whole batch
+    /// - Stores the batch and its members in one transaction
+    /// - The follow-up job is enqueued when the last member finishes
+    ///
+    /// # Returns
+    /// - `Ok(BatchResponse)` - Batch created
+    /// - `Err(ServiceError::InvalidJobs)` - Some members are invalid; nothing was created
+    #[instrument(name = "BatchService::create_batch", skip_all, fields(tenant_id = %caller.tenant_id, batch_name = %batch.name, count = batch.jobs.len()))]
+    pub async fn create_batch(&self, caller: &Caller, batch: CreateBatch) -> Result<BatchResponse, ServiceError> {
+        info!("Service: Creating batch {} with {} jobs", batch.name, batch.jobs.len());
+
+        let errors: Vec<JobError> = batch
+            .jobs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, job)| {
+                job.validate().err().map(|validation_errors| JobError {
+                    name: job.name.clone(),
+                    position: Position::Index(index),
+                    errors: validation_messages(&validation_errors),
+                })
+            })
+            .collect();
+
+        if !errors.is_empty() {
+            warn!("Service: Batch {} rejected with {} invalid jobs", batch.name, errors.len());
+            return Err(ServiceError::InvalidJobs(errors));
+        }
+
+        let on_complete = batch.on_complete.as_ref().map(serde_json::to_value).transpose()
+            .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+        let on_failure = batch.on_failure.as_ref().map(serde_json::to_value).transpose()
+            .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+        let context = enqueue_context(caller, self.idempotency_retention_hours);
+        let (batch_row, inserted) = BatchRepository::create(
+            &self.pool,
+            Uuid::new_v4(),
+            &batch.name,
+            on_complete.as_ref(),
+            on_failure.as_ref(),
+            &batch.jobs,
+            &context,
+        )
+        .await?;
+
+        let jobs: Vec<CreatedJob> = inserted
+            .into_iter()
+            .map(|(index, id)| {
+                metrics().jobs_created.with_label_values(&[batch.jobs[index].name.as_str()]).inc();
+                CreatedJob { id, position: Position::Index(index) }
+            })
+            .collect();
+
+        info!("Service: Batch {} created with {} jobs", batch_row.id, jobs.len());
+
+        Ok(BatchResponse {
+            message: format!("Batch created with {} jobs", jobs.len()),
+            batch: batch_row,
+            jobs,
+        })
+    }
+
+    /// Get a batch and its pending/succeeded/failed member counts
+    #[instrument(name = "BatchService::get_batch", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn get_batch(&self, caller: &Caller, batch_id: Uuid) -> Result<BatchStatusResponse, ServiceError> {
+        let (batch, counts) = BatchRepository::find_with_counts(&self.pool, &caller.tenant_id, batch_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or(ServiceError::BatchNotFound(batch_id))?;
+
+        Ok(BatchStatusResponse { batch, counts })
+    }
+}
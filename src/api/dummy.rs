@@ -1,6 +1,8 @@
-use actix_web::{HttpResponse, Responder, get, post, web};
+use actix_web::{HttpResponse, Responder, get, middleware::from_fn, post, web};
 use serde::Deserialize;
 
+use crate::api::auth::require_jobs_read;
+
 #[get("/")]
 async fn hello() -> impl Responder {
     HttpResponse::Ok().body("Hello world!")
@@ -47,5 +49,5 @@ fn get_scope() -> actix_web::Scope {
 }
 
 pub fn dummy_config(config: &mut web::ServiceConfig) {
-    config.service(get_scope());
+    config.service(get_scope().wrap(from_fn(require_jobs_read)));
 }
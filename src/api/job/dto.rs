@@ -1,5 +1,7 @@
-use serde::Serialize;
-use crate::db::models::JobRow;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::db::models::{JobEventMessage, JobEventRow, JobRow};
+use super::models::Job;
 
 /// Response for single job creation
 #[derive(Serialize)]
@@ -8,17 +10,145 @@ pub struct JobResponse {
     pub job: JobRow,
 }
 
+/// Where in a bulk upload a job came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Position {
+    /// 0-based item of a JSON array upload
+    Index(usize),
+    /// 1-based NDJSON line
+    Line(usize),
+    /// 1-based CSV row, counting the header
+    Row(usize),
+}
+
 /// Error details for a failed job validation
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct JobError {
     pub name: String,
+    #[serde(flatten)]
+    pub position: Position,
     pub errors: Vec<String>,
 }
 
+/// A job created by a bulk upload and the input it came from
+#[derive(Serialize)]
+pub struct CreatedJob {
+    pub id: i32,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
+/// An item of a bulk upload skipped as a duplicate, and the original job
+#[derive(Serialize)]
+pub struct DuplicateJob {
+    /// The job that already used the idempotency key or holds the unique key
+    pub id: i32,
+    #[serde(flatten)]
+    pub position: Position,
+}
+
 /// Response for bulk job creation
 #[derive(Serialize)]
 pub struct BulkJobResponse {
     pub message: String,
     pub created: usize,
+    /// Jobs skipped because their idempotency key was already used or their unique key is held
+    pub duplicates: usize,
+    /// Shared by every job of the upload; list them with `GET /jobs?batch_id=`
+    pub batch_id: Uuid,
+    pub jobs: Vec<CreatedJob>,
+    pub duplicate_jobs: Vec<DuplicateJob>,
     pub errors: Vec<JobError>,
+    /// Why a partial upload stopped early; the jobs listed above stay created
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How a bulk upload treats invalid items
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Insert the valid items and report the invalid ones
+    #[default]
+    Partial,
+    /// Insert nothing unless every item is valid
+    Atomic,
+}
+
+/// Query parameters for bulk uploads
+#[derive(Deserialize)]
+pub struct BulkUploadQuery {
+    #[serde(default)]
+    pub mode: BulkMode,
+}
+
+/// Query parameters for listing jobs
+#[derive(Deserialize)]
+pub struct ListJobsQuery {
+    pub batch_id: Uuid,
+}
+
+/// Response for a job listing
+#[derive(Serialize)]
+pub struct JobListResponse {
+    pub jobs: Vec<JobRow>,
+}
+
+/// Filters for a stream of job status transitions
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EventStreamQuery {
+    /// Only jobs with this name
+    pub name: Option<String>,
+    /// Only transitions into this status
+    pub status: Option<String>,
+}
+
+/// Response for a job's status history
+#[derive(Serialize)]
+pub struct JobEventsResponse {
+    pub job_id: i32,
+    pub events: Vec<JobEventRow>,
+}
+
+/// Message sent by a WebSocket client
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SocketRequest {
+    /// Create a job; validated like `POST /jobs`
+    Submit {
+        /// Echoed back so the client can match the reply
+        #[serde(rename = "ref", default)]
+        reference: Option<String>,
+        job: Box<Job>,
+        /// Subscribe to the new job right away so no transition is missed
+        #[serde(default)]
+        subscribe: bool,
+    },
+    /// Receive the transitions of jobs submitted on this connection
+    Subscribe { ids: Vec<i32> },
+    Unsubscribe { ids: Vec<i32> },
+}
+
+/// Message sent to a WebSocket client
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SocketMessage {
+    Submitted {
+        #[serde(rename = "ref")]
+        reference: Option<String>,
+        /// False for an idempotent replay or a held unique key
+        created: bool,
+        job: Box<JobRow>,
+    },
+    Subscribed { ids: Vec<i32> },
+    Unsubscribed { ids: Vec<i32> },
+    Event { event: JobEventMessage },
+    /// The connection fell behind and the dropped events could not be replayed; `skipped` counts events of any job
+    Lagged { skipped: u64 },
+    Error {
+        #[serde(rename = "ref")]
+        reference: Option<String>,
+        message: String,
+    },
 }
@@ -1,33 +1,64 @@
 use actix_web::{
-    HttpResponse, Responder, ResponseError, post,
-    web::{Data, ServiceConfig, scope},
+    HttpRequest, HttpResponse, Responder, ResponseError, get, post,
+    http::StatusCode,
+    middleware::from_fn,
+    web::{Bytes, Data, Path, Payload, Query, ServiceConfig, scope},
 };
 use actix_web_validator::Json;
-use actix_multipart::Multipart;
-use futures_util::StreamExt;
-use tracing::error;
+use actix_multipart::{Field, Multipart};
+use futures_util::{Stream, StreamExt};
+use tracing::{error, Instrument, Span};
+use crate::api::auth::{Caller, require_jobs_read, require_jobs_write};
+use crate::api::request_id;
 use crate::api::validation::ErrorResponse;
+use super::dto::{BulkJobResponse, BulkMode, BulkUploadQuery, EventStreamQuery, ListJobsQuery};
+use super::import::{BulkImport, LineSplitter};
 use super::models::Job;
-use super::service::JobService;
+use super::service::{JobService, ServiceError};
+use super::socket;
+use super::stream::JobEventBroadcaster;
 
-#[post("")]
+#[post("", wrap = "from_fn(require_jobs_write)")]
 async fn create_job(
+    req: HttpRequest,
     service: Data<JobService>,
+    caller: Caller,
     job: Json<Job>,
 ) -> impl Responder {
+    let mut job = job.into_inner();
+
+    // The Idempotency-Key header takes precedence over a key in the body
+    if let Some(value) = req.headers().get("Idempotency-Key") {
+        match value.to_str() {
+            Ok(key) => job.idempotency_key = Some(key.to_string()),
+            Err(_) => {
+                let error_response = ErrorResponse::new(
+                    "Invalid Idempotency-Key header",
+                    serde_json::json!({"idempotency_key": ["Must contain only visible ASCII characters"]}),
+                );
+                return HttpResponse::BadRequest().json(error_response);
+            }
+        }
+    }
+
     // Call service to create job (business logic)
-    match service.create_job(&job).await {
-        Ok(response) => HttpResponse::Created().json(response),
+    match service.create_job(&caller, &job).await {
+        Ok((response, true)) => HttpResponse::Created().json(response),
+        Ok((response, false)) => HttpResponse::Ok().json(response),
         Err(e) => e.error_response(),
     }
 }
 
-#[post("/bulk")]
+#[post("/bulk", wrap = "from_fn(require_jobs_write)")]
 async fn bulk_create_jobs(
     service: Data<JobService>,
+    caller: Caller,
+    query: Query<BulkUploadQuery>,
     mut payload: Multipart,
 ) -> impl Responder {
+    let mode = query.mode;
     let mut file_data = Vec::new();
+    let mut import = None;
 
     // Read file from multipart stream (infrastructure concern)
     // Size limit is enforced by middleware in main.rs
@@ -36,29 +67,108 @@ async fn bulk_create_jobs(
             Ok(field) => field,
             Err(err) => {
                 error!("Multipart error while reading file: {:?}", err);
-                let error_response = ErrorResponse {
-                    error: "Failed to read uploaded file".to_string(),
-                    fields: serde_json::json!({"message": "Invalid file upload"}),
-                };
+                if let Some(response) = interrupted(&mut import, StatusCode::BAD_REQUEST, "Invalid file upload") {
+                    return response;
+                }
+                let error_response = ErrorResponse::new(
+                    "Failed to read uploaded file",
+                    serde_json::json!({"message": "Invalid file upload"}),
+                );
                 return HttpResponse::BadRequest().json(error_response);
             }
         };
 
+        // A JSON array is only parsed after the whole body, so it cannot join a streaming import
+        let is_csv = is_csv(&field);
+        let is_ndjson = is_ndjson(&field);
+        if (is_ndjson || is_csv) && !is_blank(&file_data) {
+            return mixed_upload(&mut import);
+        }
+
+        // NDJSON is parsed and inserted line by line as it arrives
+        if is_ndjson {
+            let bulk = import.get_or_insert_with(|| BulkImport::new(&service, &caller, mode));
+            let mut lines = LineSplitter::default();
+
+            while let Some(chunk) = field.next().await {
+                let data = match chunk {
+                    Ok(data) => data,
+                    Err(err) => {
+                        error!("Chunk read error: {:?}", err);
+                        if let Some(response) = interrupted(&mut import, StatusCode::BAD_REQUEST, "Error reading file") {
+                            return response;
+                        }
+                        let error_response = ErrorResponse::new(
+                            "Failed to read file content",
+                            serde_json::json!({"message": "Error reading file"}),
+                        );
+                        return HttpResponse::BadRequest().json(error_response);
+                    }
+                };
+
+                for (line, bytes) in lines.feed(&data) {
+                    if let Err(e) = bulk.push_line(line, &bytes).await {
+                        return import_failed(&mut import, e);
+                    }
+                }
+            }
+
+            if let Some((line, bytes)) = lines.finish() {
+                if let Err(e) = bulk.push_line(line, &bytes).await {
+                    return import_failed(&mut import, e);
+                }
+            }
+
+            continue;
+        }
+
+        let field_start = file_data.len();
+
         // Read field data (file content)
         while let Some(chunk) = field.next().await {
             let data = match chunk {
                 Ok(data) => data,
                 Err(err) => {
                     error!("Chunk read error: {:?}", err);
-                    let error_response = ErrorResponse {
-                        error: "Failed to read file content".to_string(),
-                        fields: serde_json::json!({"message": "Error reading file"}),
-                    };
+                    if let Some(response) = interrupted(&mut import, StatusCode::BAD_REQUEST, "Error reading file") {
+                        return response;
+                    }
+                    let error_response = ErrorResponse::new(
+                        "Failed to read file content",
+                        serde_json::json!({"message": "Error reading file"}),
+                    );
                     return HttpResponse::BadRequest().json(error_response);
                 }
             };
             file_data.extend_from_slice(&data);
         }
+
+        if !is_csv && import.is_some() && !is_blank(&file_data[field_start..]) {
+            return mixed_upload(&mut import);
+        }
+
+        // CSV records may span lines, so the file is parsed once fully read
+        if is_csv {
+            let bulk = import.get_or_insert_with(|| BulkImport::new(&service, &caller, mode));
+            if let Err(e) = bulk.push_csv(&file_data[field_start..]).await {
+                return import_failed(&mut import, e);
+            }
+            file_data.truncate(field_start);
+        }
+    }
+
+    if let Some(bulk) = import.as_mut().filter(|_| mode == BulkMode::Partial) {
+        // Flush here so a failing last chunk still reports the chunks before it
+        if let Err(e) = bulk.flush().await {
+            return import_failed(&mut import, e);
+        }
+    }
+
+    if let Some(import) = import {
+        return match import.finish().await {
+            Ok(response) => bulk_response(mode, response),
+            Err(e) => e.error_response(),
+        };
     }
 
     // Parse JSON array from file
@@ -66,25 +176,205 @@ async fn bulk_create_jobs(
         Ok(jobs) => jobs,
         Err(err) => {
             error!("JSON parse error: {:?}", err);
-            let error_response = ErrorResponse {
-                error: "Failed to parse JSON file".to_string(),
-                fields: serde_json::json!({"message": format!("Invalid JSON: {}", err)}),
-            };
+            let error_response = ErrorResponse::new(
+                "Failed to parse JSON file",
+                serde_json::json!({"message": format!("Invalid JSON: {}", err)}),
+            );
             return HttpResponse::BadRequest().json(error_response);
         }
     };
 
     // Call service to handle business logic (validation + bulk insert)
-    match service.bulk_create_jobs(jobs).await {
+    match service.bulk_create_jobs(&caller, jobs, mode).await {
+        Ok(response) => bulk_response(mode, response),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Summary of a partial streaming import that stopped early, with `status`
+///
+/// Jobs of chunks inserted before the failure stay created, so the client gets
+/// their ids instead of a bare error. `None` when there is no partial import.
+fn interrupted(import: &mut Option<BulkImport<'_>>, status: StatusCode, error: &str) -> Option<HttpResponse> {
+    let summary = import.take()?.interrupt(error.to_string())?;
+    Some(HttpResponse::build(status).json(summary))
+}
+
+/// Reject an upload mixing a JSON array with NDJSON or CSV files
+///
+/// An import already under way in partial mode reports what it inserted.
+fn mixed_upload(import: &mut Option<BulkImport<'_>>) -> HttpResponse {
+    const MESSAGE: &str = "A JSON array cannot be uploaded together with NDJSON or CSV files";
+    interrupted(import, StatusCode::BAD_REQUEST, MESSAGE).unwrap_or_else(|| {
+        let error_response = ErrorResponse::new("Mixed upload formats", serde_json::json!({"message": MESSAGE}));
+        HttpResponse::BadRequest().json(error_response)
+    })
+}
+
+/// Field data with nothing but whitespace, e.g. an empty form field
+fn is_blank(data: &[u8]) -> bool {
+    data.iter().all(u8::is_ascii_whitespace)
+}
+
+/// Respond to a failed streaming import step, keeping the partial summary if any
+fn import_failed(import: &mut Option<BulkImport<'_>>, e: ServiceError) -> HttpResponse {
+    let response = e.error_response();
+    interrupted(import, response.status(), &e.to_string()).unwrap_or(response)
+}
+
+/// A rejected atomic upload is a client error; partial uploads always succeed
+fn bulk_response(mode: BulkMode, response: BulkJobResponse) -> HttpResponse {
+    if mode == BulkMode::Atomic && !response.errors.is_empty() {
+        HttpResponse::UnprocessableEntity().json(response)
+    } else {
+        HttpResponse::Ok().json(response)
+    }
+}
+
+/// CSV uploads are recognized by content type or file extension
+fn is_csv(field: &Field) -> bool {
+    let by_type = field
+        .content_type()
+        .map(|mime| mime.essence_str() == "text/csv")
+        .unwrap_or(false);
+
+    let by_extension = field
+        .content_disposition()
+        .and_then(|disposition| disposition.get_filename())
+        .map(|filename| filename.ends_with(".csv"))
+        .unwrap_or(false);
+
+    by_type || by_extension
+}
+
+/// NDJSON uploads are recognized by content type or file extension
+fn is_ndjson(field: &Field) -> bool {
+    let by_type = field
+        .content_type()
+        .map(|mime| matches!(mime.essence_str(), "application/x-ndjson" | "application/jsonl"))
+        .unwrap_or(false);
+
+    let by_extension = field
+        .content_disposition()
+        .and_then(|disposition| disposition.get_filename())
+        .map(|filename| filename.ends_with(".ndjson") || filename.ends_with(".jsonl"))
+        .unwrap_or(false);
+
+    by_type || by_extension
+}
+
+#[get("", wrap = "from_fn(require_jobs_read)")]
+async fn list_jobs(
+    service: Data<JobService>,
+    caller: Caller,
+    query: Query<ListJobsQuery>,
+) -> impl Responder {
+    match service.list_jobs_by_batch(&caller, query.batch_id).await {
         Ok(response) => HttpResponse::Ok().json(response),
         Err(e) => e.error_response(),
     }
 }
 
+#[get("/{id}/events", wrap = "from_fn(require_jobs_read)")]
+async fn get_job_events(
+    service: Data<JobService>,
+    caller: Caller,
+    path: Path<i32>,
+) -> impl Responder {
+    match service.get_job_events(&caller, path.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Live SSE stream of every job's status transitions, filtered by `name` and `status`
+#[get("/events", wrap = "from_fn(require_jobs_read)")]
+async fn stream_job_events(
+    req: HttpRequest,
+    service: Data<JobService>,
+    broadcaster: Data<JobEventBroadcaster>,
+    caller: Caller,
+    query: Query<EventStreamQuery>,
+) -> impl Responder {
+    let last_event_id = last_event_id(&req);
+    match service.stream_events(&caller, &broadcaster, None, query.into_inner(), last_event_id).await {
+        Ok(stream) => event_stream_response(stream),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Live SSE stream of one job's status transitions
+#[get("/{id}/events/stream", wrap = "from_fn(require_jobs_read)")]
+async fn stream_single_job_events(
+    req: HttpRequest,
+    service: Data<JobService>,
+    broadcaster: Data<JobEventBroadcaster>,
+    caller: Caller,
+    path: Path<i32>,
+    query: Query<EventStreamQuery>,
+) -> impl Responder {
+    let last_event_id = last_event_id(&req);
+    match service
+        .stream_events(&caller, &broadcaster, Some(path.into_inner()), query.into_inner(), last_event_id)
+        .await
+    {
+        Ok(stream) => event_stream_response(stream),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Event id an `EventSource` sends when it reconnects; unparsable values are ignored
+fn last_event_id(req: &HttpRequest) -> Option<i64> {
+    req.headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+}
+
+fn event_stream_response<S>(stream: S) -> HttpResponse
+where
+    S: Stream<Item = Result<Bytes, actix_web::Error>> + 'static,
+{
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        // Keep nginx-style proxies from buffering the stream
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(stream)
+}
+
+/// WebSocket for submitting jobs and following the ones submitted on it
+#[get("/ws", wrap = "from_fn(require_jobs_write)")]
+async fn job_socket(
+    req: HttpRequest,
+    body: Payload,
+    service: Data<JobService>,
+    broadcaster: Data<JobEventBroadcaster>,
+    caller: Caller,
+) -> impl Responder {
+    let (response, session, messages) = match actix_ws::handle(&req, body) {
+        Ok(upgrade) => upgrade,
+        Err(e) => return e.error_response(),
+    };
+
+    // Subscribe before the first submission so none of its transitions are missed
+    let events = broadcaster.subscribe();
+    let connection = socket::run(service, caller, session, messages, events);
+    let request_id = request_id::current().unwrap_or_default();
+    actix_web::rt::spawn(request_id::scope(request_id, connection).instrument(Span::current()));
+
+    response
+}
+
 pub fn job_config(config: &mut ServiceConfig) {
     config.service(
         scope("jobs")
             .service(create_job)
+            .service(list_jobs)
             .service(bulk_create_jobs)
+            .service(stream_job_events)
+            .service(get_job_events)
+            .service(stream_single_job_events)
+            .service(job_socket)
     );
 }
@@ -0,0 +1,446 @@
+use serde_json::{Map, Value};
+use tracing::{info, warn};
+use validator::Validate;
+
+use uuid::Uuid;
+
+use crate::api::auth::Caller;
+use super::dto::{BulkJobResponse, BulkMode, CreatedJob, DuplicateJob, JobError, Position};
+use super::models::Job;
+use super::service::{rejected_bulk_response, validation_messages, JobService, ServiceError};
+
+/// Number of valid jobs inserted per transaction during a streaming import
+const CHUNK_SIZE: usize = 1000;
+
+/// CSV columns that map directly onto `Job` fields
+const CSV_JOB_COLUMNS: &[&str] = &[
+    "name",
+    "status",
+    "idempotency_key",
+    "unique_key",
+    "unique_scope",
+    "unique_window_secs",
+    "on_parent_failure",
+    "callback_url",
+];
+
+/// Prefix of CSV columns collected into the job's payload object
+const CSV_PAYLOAD_PREFIX: &str = "payload.";
+
+/// Splits a byte stream into lines without buffering more than one partial line
+#[derive(Default)]
+pub struct LineSplitter {
+    partial: Vec<u8>,
+    line: usize,
+}
+
+impl LineSplitter {
+    /// Feed the next chunk and return every line it completes, with 1-based line numbers
+    pub fn feed(&mut self, data: &[u8]) -> Vec<(usize, Vec<u8>)> {
+        let mut lines = Vec::new();
+
+        for part in data.split_inclusive(|b| *b == b'\n') {
+            self.partial.extend_from_slice(part);
+
+            if part.ends_with(b"\n") {
+                self.line += 1;
+                lines.push((self.line, std::mem::take(&mut self.partial)));
+            }
+        }
+
+        lines
+    }
+
+    /// Return the trailing line if the stream did not end with a newline
+    pub fn finish(mut self) -> Option<(usize, Vec<u8>)> {
+        if self.partial.is_empty() {
+            return None;
+        }
+
+        self.line += 1;
+        Some((self.line, self.partial))
+    }
+}
+
+/// Incremental bulk import
+///
+/// Jobs are parsed and validated one line or row at a time and inserted in chunks of
+/// `CHUNK_SIZE`, so memory use does not grow with the upload. Each chunk is its
+/// own transaction: when a chunk fails, earlier chunks stay inserted. All chunks
+/// share one batch id.
+///
+/// In atomic mode nothing is inserted until the whole upload has been read and
+/// validated; the valid jobs are then held in memory and inserted in a single
+/// transaction, or dropped if any item failed.
+pub struct BulkImport<'a> {
+    service: &'a JobService,
+    caller: &'a Caller,
+    mode: BulkMode,
+    batch_id: Uuid,
+    pending: Vec<Job>,
+    pending_positions: Vec<Position>,
+    created: Vec<CreatedJob>,
+    duplicates: Vec<DuplicateJob>,
+    errors: Vec<JobError>,
+}
+
+impl<'a> BulkImport<'a> {
+    pub fn new(service: &'a JobService, caller: &'a Caller, mode: BulkMode) -> Self {
+        Self {
+            service,
+            caller,
+            mode,
+            batch_id: Uuid::new_v4(),
+            pending: Vec::with_capacity(CHUNK_SIZE),
+            pending_positions: Vec::with_capacity(CHUNK_SIZE),
+            created: Vec::new(),
+            duplicates: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Parse and validate one NDJSON line; blank lines are ignored
+    pub async fn push_line(&mut self, line: usize, bytes: &[u8]) -> Result<(), ServiceError> {
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+
+        match serde_json::from_slice::<Job>(bytes) {
+            Ok(job) => self.push(Position::Line(line), job).await,
+            Err(err) => {
+                warn!("Service: Invalid JSON on line {}: {}", line, err);
+                self.errors.push(JobError {
+                    name: String::new(),
+                    position: Position::Line(line),
+                    errors: vec![format!("Invalid JSON: {}", err)],
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Parse a CSV upload whose header row names `Job` fields and `payload.*` columns
+    ///
+    /// Empty cells are treated as absent. Unknown columns reject the whole file.
+    pub async fn push_csv(&mut self, data: &[u8]) -> Result<(), ServiceError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(data);
+
+        let headers = csv_headers(&mut reader)?;
+
+        for (index, record) in reader.records().enumerate() {
+            let position = csv_row(index, &record);
+
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    warn!("Service: Invalid CSV record at {:?}: {}", position, err);
+                    self.errors.push(JobError {
+                        name: String::new(),
+                        position,
+                        errors: vec![format!("Invalid CSV: {}", err)],
+                    });
+                    continue;
+                }
+            };
+
+            let value = csv_record_to_value(&headers, &record);
+            let name = value.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+
+            match serde_json::from_value::<Job>(value) {
+                Ok(job) => self.push(position, job).await?,
+                Err(err) => {
+                    warn!("Service: Invalid CSV job at {:?}: {}", position, err);
+                    self.errors.push(JobError {
+                        name,
+                        position,
+                        errors: vec![err.to_string()],
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate a parsed job and queue it for the next chunk
+    pub async fn push(&mut self, position: Position, job: Job) -> Result<(), ServiceError> {
+        if let Err(validation_errors) = job.validate() {
+            warn!("Service: Validation failed for job at {:?}: {}", position, job.name);
+            self.errors.push(JobError {
+                name: job.name,
+                position,
+                errors: validation_messages(&validation_errors),
+            });
+            return Ok(());
+        }
+
+        match self.mode {
+            BulkMode::Partial => {
+                self.pending.push(job);
+                self.pending_positions.push(position);
+
+                if self.pending.len() >= CHUNK_SIZE {
+                    self.flush().await?;
+                }
+            }
+            // Once anything failed the upload will be rejected; stop holding jobs
+            BulkMode::Atomic if self.errors.is_empty() => {
+                self.pending.push(job);
+                self.pending_positions.push(position);
+            }
+            BulkMode::Atomic => {}
+        }
+
+        Ok(())
+    }
+
+    /// Insert the remaining jobs and summarize the import
+    pub async fn finish(mut self) -> Result<BulkJobResponse, ServiceError> {
+        if self.mode == BulkMode::Atomic && !self.errors.is_empty() {
+            warn!("Service: Atomic import rejected with {} errors", self.errors.len());
+            return Ok(rejected_bulk_response(self.batch_id, self.errors));
+        }
+
+        self.flush().await?;
+
+        info!(
+            "Service: Streaming import completed: {} created, {} duplicates, {} failed",
+            self.created.len(), self.duplicates.len(), self.errors.len()
+        );
+
+        Ok(self.summary("completed", None))
+    }
+
+    /// Summarize a partial import that stopped early, or `None` in atomic mode
+    ///
+    /// Chunks inserted before the failure stay committed, so the summary lists
+    /// their jobs alongside `error`. Atomic imports have inserted nothing yet.
+    pub fn interrupt(self, error: String) -> Option<BulkJobResponse> {
+        if self.mode == BulkMode::Atomic {
+            return None;
+        }
+
+        warn!(
+            "Service: Streaming import stopped after {} created: {}",
+            self.created.len(), error
+        );
+
+        Some(self.summary("stopped", Some(error)))
+    }
+
+    fn summary(self, outcome: &str, error: Option<String>) -> BulkJobResponse {
+        let created_count = self.created.len();
+        let error_count = self.errors.len();
+        let duplicate_count = self.duplicates.len();
+
+        BulkJobResponse {
+            message: format!(
+                "Bulk job creation {}. {} created, {} duplicates, {} failed",
+                outcome,
+                created_count,
+                duplicate_count,
+                error_count
+            ),
+            created: created_count,
+            duplicates: duplicate_count,
+            batch_id: self.batch_id,
+            jobs: self.created,
+            duplicate_jobs: self.duplicates,
+            errors: self.errors,
+            error,
+        }
+    }
+
+    /// Insert the jobs queued since the last chunk
+    pub async fn flush(&mut self) -> Result<(), ServiceError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let (created, duplicates) = self
+            .service
+            .insert_valid_jobs(self.caller, self.batch_id, &self.pending, &self.pending_positions)
+            .await?;
+        self.created.extend(created);
+        self.duplicates.extend(duplicates);
+        self.pending.clear();
+        self.pending_positions.clear();
+
+        Ok(())
+    }
+}
+
+/// Read the header row, rejecting columns that are neither `Job` fields nor `payload.*`
+fn csv_headers<R: std::io::Read>(reader: &mut csv::Reader<R>) -> Result<csv::StringRecord, ServiceError> {
+    let headers = reader
+        .headers()
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid CSV header: {}", e)))?
+        .clone();
+
+    for column in headers.iter() {
+        if !CSV_JOB_COLUMNS.contains(&column) && !column.starts_with(CSV_PAYLOAD_PREFIX) {
+            return Err(ServiceError::ValidationError(format!("Unknown CSV column: {}", column)));
+        }
+    }
+
+    Ok(headers)
+}
+
+/// Spreadsheet row of the `index`-th record
+///
+/// Header is row 1; fall back to counting when the reader has no position.
+fn csv_row(index: usize, record: &Result<csv::StringRecord, csv::Error>) -> Position {
+    Position::Row(
+        record
+            .as_ref()
+            .ok()
+            .and_then(|r| r.position())
+            .map(|p| p.line() as usize)
+            .unwrap_or(index + 2),
+    )
+}
+
+/// Build the JSON shape of a `Job` from one CSV record
+fn csv_record_to_value(headers: &csv::StringRecord, record: &csv::StringRecord) -> Value {
+    let mut job = Map::new();
+    let mut payload = Map::new();
+
+    for (column, cell) in headers.iter().zip(record.iter()) {
+        if cell.is_empty() {
+            continue;
+        }
+
+        if let Some(key) = column.strip_prefix(CSV_PAYLOAD_PREFIX) {
+            payload.insert(key.to_string(), Value::String(cell.to_string()));
+        } else if column == "unique_window_secs" {
+            // Numeric column; leave unparsable cells as text so deserialization reports them
+            let value = cell
+                .parse::<u64>()
+                .map(Value::from)
+                .unwrap_or_else(|_| Value::String(cell.to_string()));
+            job.insert(column.to_string(), value);
+        } else {
+            job.insert(column.to_string(), Value::String(cell.to_string()));
+        }
+    }
+
+    if !payload.is_empty() {
+        job.insert("payload".to_string(), Value::Object(payload));
+    }
+
+    Value::Object(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(chunks: &[&[u8]]) -> Vec<(usize, Vec<u8>)> {
+        let mut splitter = LineSplitter::default();
+        let mut lines: Vec<_> = chunks.iter().flat_map(|chunk| splitter.feed(chunk)).collect();
+        lines.extend(splitter.finish());
+        lines
+    }
+
+    #[test]
+    fn line_splitter_joins_lines_across_chunks() {
+        let lines = split(&[b"{\"name\":", b"\"first\"}\n{\"na", b"me\":\"second\"}\n"]);
+
+        assert_eq!(
+            lines,
+            vec![
+                (1, b"{\"name\":\"first\"}\n".to_vec()),
+                (2, b"{\"name\":\"second\"}\n".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn line_splitter_numbers_crlf_lines_once() {
+        let lines = split(&[
+            b"{\"name\":\"first\",\"status\":\"new\"}\r",
+            b"\n\r\n{\"name\":\"third\",\"status\":\"new\"}\r\n",
+        ]);
+
+        assert_eq!(lines.iter().map(|(line, _)| *line).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(lines[1].1, b"\r\n");
+        // The trailing CR is JSON whitespace, so the line still parses
+        let job: Job = serde_json::from_slice(&lines[2].1).unwrap();
+        assert_eq!(job.name, "third");
+    }
+
+    #[test]
+    fn line_splitter_returns_final_unterminated_line() {
+        let lines = split(&[b"one\ntw", b"o"]);
+
+        assert_eq!(lines, vec![(1, b"one\n".to_vec()), (2, b"two".to_vec())]);
+        assert_eq!(split(&[b"one\n"]), vec![(1, b"one\n".to_vec())]);
+    }
+
+    fn csv_reader(data: &str) -> csv::Reader<&[u8]> {
+        csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data.as_bytes())
+    }
+
+    #[test]
+    fn csv_maps_columns_onto_job_fields() {
+        let mut reader = csv_reader(
+            "name, status ,unique_window_secs,unique_key,payload.customer_id\n\
+             reindex,new,60,,42\n\
+             resize,new,soon,img:7,\n",
+        );
+        let headers = csv_headers(&mut reader).unwrap();
+        let values: Vec<Value> = reader
+            .records()
+            .map(|record| csv_record_to_value(&headers, &record.unwrap()))
+            .collect();
+
+        assert_eq!(
+            values[0],
+            serde_json::json!({
+                "name": "reindex",
+                "status": "new",
+                "unique_window_secs": 60,
+                "payload": {"customer_id": "42"}
+            })
+        );
+        // Empty cells are absent and unparsable numbers are left for deserialization to report
+        assert_eq!(
+            values[1],
+            serde_json::json!({
+                "name": "resize",
+                "status": "new",
+                "unique_window_secs": "soon",
+                "unique_key": "img:7"
+            })
+        );
+        assert!(serde_json::from_value::<Job>(values[0].clone()).is_ok());
+        assert!(serde_json::from_value::<Job>(values[1].clone()).is_err());
+    }
+
+    #[test]
+    fn csv_rejects_unknown_columns() {
+        let err = csv_headers(&mut csv_reader("name,status,priority\nreindex,new,1\n")).unwrap_err();
+
+        assert!(matches!(err, ServiceError::ValidationError(message) if message == "Unknown CSV column: priority"));
+    }
+
+    #[test]
+    fn csv_rows_follow_spreadsheet_lines() {
+        let mut reader = csv_reader(
+            "name,status,payload.note\n\
+             first,new,\"spans\ntwo lines\"\n\
+             second,new,\n\
+             third,new,ok\n",
+        );
+        csv_headers(&mut reader).unwrap();
+        let rows: Vec<Position> = reader
+            .records()
+            .enumerate()
+            .map(|(index, record)| csv_row(index, &record))
+            .collect();
+
+        assert_eq!(rows, vec![Position::Row(2), Position::Row(4), Position::Row(5)]);
+    }
+}
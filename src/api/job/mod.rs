@@ -1,6 +1,9 @@
 pub mod models;
 pub mod dto;
 pub mod handlers;
+pub mod import;
+pub mod socket;
+pub mod stream;
 pub mod service;
 
 // Re-export commonly used types
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 /// Job status enum representing the state of a job
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
@@ -9,10 +9,58 @@ pub enum JobStatus {
     Processing,
     Success,
     Failed,
+    /// Waiting for `depends_on` parents; set by the server
+    Blocked,
+    /// A parent failed; set by the server
+    Cancelled,
+}
+
+/// What happens to a waiting job when one of its parents fails
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParentFailure {
+    /// Cancel the job (and, in turn, its own waiting children)
+    #[default]
+    Cancel,
+    /// Run the job once every parent has finished, whatever the outcome
+    RunAnyway,
+}
+
+impl ParentFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParentFailure::Cancel => "cancel",
+            ParentFailure::RunAnyway => "run_anyway",
+        }
+    }
+}
+
+/// How long a job holds its `unique_key`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum UniqueScope {
+    /// Until a worker picks the job up
+    Queued,
+    /// Until the job succeeds or fails
+    #[default]
+    QueuedOrRunning,
+    /// For `unique_window_secs` after creation, whatever the job's status
+    Window,
+}
+
+impl UniqueScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UniqueScope::Queued => "queued",
+            UniqueScope::QueuedOrRunning => "queued_or_running",
+            UniqueScope::Window => "window",
+        }
+    }
 }
 
 /// Job model for creating and validating jobs
 #[derive(Deserialize, Serialize, Debug, Validate)]
+#[validate(schema(function = "validate_job"))]
 pub struct Job {
     #[validate(length(
         min = 3,
@@ -21,4 +69,104 @@ pub struct Job {
     ))]
     pub name: String,
     pub status: JobStatus,
+    /// Repeated submissions with the same key return the original job.
+    /// Set from the `Idempotency-Key` header for `POST /jobs`, or per item in bulk files.
+    #[serde(default)]
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Idempotency key must be between 1 and 255 characters"
+    ))]
+    pub idempotency_key: Option<String>,
+    /// Only one job per tenant may hold this key while its `unique_scope` lasts.
+    /// Submitting another returns the job that holds it.
+    #[serde(default)]
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Unique key must be between 1 and 255 characters"
+    ))]
+    pub unique_key: Option<String>,
+    #[serde(default)]
+    pub unique_scope: UniqueScope,
+    /// Length of the hold for the `window` scope
+    #[serde(default)]
+    #[validate(range(min = 1, message = "Unique window must be at least 1 second"))]
+    pub unique_window_secs: Option<u32>,
+    /// Arbitrary input for the job's handler
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    /// Ids of jobs that must succeed before this one runs
+    #[serde(default)]
+    #[validate(length(max = 100, message = "A job can depend on at most 100 jobs"))]
+    pub depends_on: Vec<i32>,
+    #[serde(default)]
+    pub on_parent_failure: ParentFailure,
+    /// Receives a signed POST with the job's outcome when it finishes
+    #[serde(default)]
+    #[validate(custom(function = "validate_callback_url"))]
+    pub callback_url: Option<String>,
+}
+
+impl Job {
+    /// The key to lock at insert, if the job starts inside its unique scope
+    pub fn initial_unique_lock(&self) -> Option<&str> {
+        let held = match self.unique_scope {
+            UniqueScope::Queued => matches!(self.status, JobStatus::New),
+            UniqueScope::QueuedOrRunning => matches!(self.status, JobStatus::New | JobStatus::Processing),
+            UniqueScope::Window => true,
+        };
+
+        self.unique_key.as_deref().filter(|_| held)
+    }
+}
+
+/// Cross-field rules of a submitted job
+fn validate_job(job: &Job) -> Result<(), ValidationError> {
+    validate_unique_window(job)?;
+    validate_dependencies(job)
+}
+
+/// `unique_window_secs` is required by, and only allowed with, the `window` scope
+fn validate_unique_window(job: &Job) -> Result<(), ValidationError> {
+    let is_window = job.unique_scope == UniqueScope::Window;
+
+    if is_window != job.unique_window_secs.is_some() {
+        let mut error = ValidationError::new("unique_window");
+        error.message = Some("unique_window_secs must be set exactly when unique_scope is window".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Callbacks must be absolute http(s) URLs; the dispatcher never follows redirects
+fn validate_callback_url(url: &str) -> Result<(), ValidationError> {
+    let valid = reqwest::Url::parse(url)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some());
+
+    if !valid {
+        let mut error = ValidationError::new("callback_url");
+        error.message = Some("callback_url must be an http or https URL".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// Blocked and cancelled are reached only through dependencies, which need a `new` job
+fn validate_dependencies(job: &Job) -> Result<(), ValidationError> {
+    if matches!(job.status, JobStatus::Blocked | JobStatus::Cancelled) {
+        let mut error = ValidationError::new("status");
+        error.message = Some("Status blocked and cancelled are set by the server".into());
+        return Err(error);
+    }
+
+    if !job.depends_on.is_empty() && !matches!(job.status, JobStatus::New) {
+        let mut error = ValidationError::new("depends_on");
+        error.message = Some("Only jobs submitted as new can have depends_on".into());
+        return Err(error);
+    }
+
+    Ok(())
 }
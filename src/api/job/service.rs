@@ -1,13 +1,21 @@
-use actix_web::{HttpResponse, ResponseError};
+use actix_web::{HttpResponse, ResponseError, web::Bytes};
+use futures_util::Stream;
 use sqlx::{Pool, Postgres};
 use std::fmt;
-use tracing::{error, info, warn};
-use validator::Validate;
+use tracing::{error, info, instrument, warn};
+use uuid::Uuid;
+use validator::{Validate, ValidationErrors};
 
+use crate::api::auth::Caller;
+use crate::api::request_id;
 use crate::api::validation::ErrorResponse;
-use crate::db::job_repository::JobRepository;
-use super::dto::{BulkJobResponse, JobError, JobResponse};
+use crate::db::job_repository::{InsertError, JobRepository};
+use crate::db::models::{EnqueueContext, JobEventMessage};
+use crate::metrics::metrics;
+use crate::telemetry;
+use super::dto::{BulkJobResponse, BulkMode, CreatedJob, DuplicateJob, EventStreamQuery, JobError, JobEventsResponse, JobListResponse, JobResponse, Position};
 use super::models::Job;
+use super::stream::{event_stream, EventFilter, JobEventBroadcaster};
 
 /// Service-level errors
 #[derive(Debug)]
@@ -20,6 +28,51 @@ pub enum ServiceError {
 
     /// Job not found
     NotFound(i32),
+
+    /// Batch not found
+    BatchNotFound(Uuid),
+
+    /// Workflow not found
+    WorkflowNotFound(Uuid),
+
+    /// Workflow already finished or cancelled
+    WorkflowFinished(Uuid),
+
+    /// Queue to resume is not paused
+    QueueNotPaused(String),
+
+    /// Some jobs of an all-or-nothing submission are invalid
+    InvalidJobs(Vec<JobError>),
+
+    /// Tenant quota would be exceeded
+    QuotaExceeded(String),
+
+    /// The submission conflicts with stored data in a way that is not a duplicate
+    Conflict(String),
+}
+
+impl From<InsertError> for ServiceError {
+    fn from(e: InsertError) -> Self {
+        match e {
+            InsertError::Database(e) => ServiceError::DatabaseError(e),
+            InsertError::QuotaExceeded { limit, queued } => ServiceError::QuotaExceeded(format!(
+                "Queued job quota exceeded: {} of {} queued jobs in use",
+                queued, limit
+            )),
+            InsertError::UnknownParents(ids) => ServiceError::ValidationError(format!(
+                "depends_on references unknown jobs: {:?}",
+                ids
+            )),
+            InsertError::DependencyCycle(id) => ServiceError::ValidationError(format!(
+                "depends_on would create a cycle through job {}",
+                id
+            )),
+            InsertError::UnexpectedConflict(count) => ServiceError::Conflict(format!(
+                "{} jobs conflict with existing jobs",
+                count
+            )),
+        }
+    }
 }
 
 impl fmt::Display for ServiceError {
@@ -28,6 +81,13 @@ impl fmt::Display for ServiceError {
             ServiceError::DatabaseError(e) => write!(f, "Database error: {}", e),
             ServiceError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             ServiceError::NotFound(id) => write!(f, "Job not found: {}", id),
+            ServiceError::BatchNotFound(id) => write!(f, "Batch not found: {}", id),
+            ServiceError::WorkflowNotFound(id) => write!(f, "Workflow not found: {}", id),
+            ServiceError::WorkflowFinished(id) => write!(f, "Workflow already finished: {}", id),
+            ServiceError::QueueNotPaused(name) => write!(f, "Queue is not paused: {}", name),
+            ServiceError::InvalidJobs(errors) => write!(f, "{} invalid jobs", errors.len()),
+            ServiceError::QuotaExceeded(msg) => write!(f, "Quota exceeded: {}", msg),
+            ServiceError::Conflict(msg) => write!(f, "Conflict: {}", msg),
         }
     }
 }
@@ -39,24 +99,73 @@ impl ResponseError for ServiceError {
         match self {
             ServiceError::DatabaseError(e) => {
                 error!("Database error: {}", e);
-                HttpResponse::InternalServerError().json(ErrorResponse {
-                    error: "Failed to process request".to_string(),
-                    fields: serde_json::json!({"message": "Database error occurred"}),
-                })
+                HttpResponse::InternalServerError().json(ErrorResponse::new(
+                    "Failed to process request",
+                    serde_json::json!({"message": "Database error occurred"}),
+                ))
             }
             ServiceError::ValidationError(msg) => {
                 warn!("Validation error: {}", msg);
-                HttpResponse::BadRequest().json(ErrorResponse {
-                    error: "Validation failed".to_string(),
-                    fields: serde_json::json!({"message": msg}),
-                })
+                HttpResponse::BadRequest().json(ErrorResponse::new(
+                    "Validation failed",
+                    serde_json::json!({"message": msg}),
+                ))
             }
             ServiceError::NotFound(id) => {
                 warn!("Job not found: {}", id);
-                HttpResponse::NotFound().json(ErrorResponse {
-                    error: "Not found".to_string(),
-                    fields: serde_json::json!({"message": format!("Job with id {} not found", id)}),
-                })
+                HttpResponse::NotFound().json(ErrorResponse::new(
+                    "Not found",
+                    serde_json::json!({"message": format!("Job with id {} not found", id)}),
+                ))
+            }
+            ServiceError::BatchNotFound(id) => {
+                warn!("Batch not found: {}", id);
+                HttpResponse::NotFound().json(ErrorResponse::new(
+                    "Not found",
+                    serde_json::json!({"message": format!("Batch with id {} not found", id)}),
+                ))
+            }
+            ServiceError::WorkflowNotFound(id) => {
+                warn!("Workflow not found: {}", id);
+                HttpResponse::NotFound().json(ErrorResponse::new(
+                    "Not found",
+                    serde_json::json!({"message": format!("Workflow with id {} not found", id)}),
+                ))
+            }
+            ServiceError::WorkflowFinished(id) => {
+                warn!("Workflow already finished: {}", id);
+                HttpResponse::Conflict().json(ErrorResponse::new(
+                    "Conflict",
+                    serde_json::json!({"message": format!("Workflow with id {} has already finished", id)}),
+                ))
+            }
+            ServiceError::QueueNotPaused(name) => {
+                warn!("Queue is not paused: {}", name);
+                HttpResponse::NotFound().json(ErrorResponse::new(
+                    "Not found",
+                    serde_json::json!({"message": format!("Queue is not paused: {}", name)}),
+                ))
+            }
+            ServiceError::InvalidJobs(errors) => {
+                warn!("Rejected submission with {} invalid jobs", errors.len());
+                HttpResponse::UnprocessableEntity().json(ErrorResponse::new(
+                    "Validation failed",
+                    serde_json::json!({"jobs": errors}),
+                ))
+            }
+            ServiceError::QuotaExceeded(msg) => {
+                warn!("Quota exceeded: {}", msg);
+                HttpResponse::TooManyRequests().json(ErrorResponse::new(
+                    "Quota exceeded",
+                    serde_json::json!({"message": msg}),
+                ))
+            }
+            ServiceError::Conflict(msg) => {
+                error!("Unexpected conflict: {}", msg);
+                HttpResponse::Conflict().json(ErrorResponse::new(
+                    "Conflict",
+                    serde_json::json!({"message": msg}),
+                ))
             }
         }
     }
@@ -65,12 +174,13 @@ impl ResponseError for ServiceError {
 /// Job service containing business logic
 pub struct JobService {
     pool: Pool<Postgres>,
+    idempotency_retention_hours: i32,
 }
 
 impl JobService {
     /// Create a new JobService instance
-    pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
+    pub fn new(pool: Pool<Postgres>, idempotency_retention_hours: i32) -> Self {
+        Self { pool, idempotency_retention_hours }
     }
 
     /// Create a single job
@@ -78,84 +188,188 @@ impl JobService {
     /// # Business Logic
     /// - Validates the job
     /// - Creates job in database
+    /// - Returns the original job when the idempotency key was already used
+    ///   or another job still holds the unique key
     /// - Logs the operation
     ///
     /// # Returns
-    /// - `Ok(JobResponse)` - Job created successfully
+    /// - `Ok((JobResponse, true))` - Job created successfully
+    /// - `Ok((JobResponse, false))` - Idempotent replay or unique key already held
     /// - `Err(ServiceError)` - Creation failed
-    pub async fn create_job(&self, job: &Job) -> Result<JobResponse, ServiceError> {
-        info!("Service: Creating job with name={}", job.name);
+    #[instrument(name = "JobService::create_job", skip_all, fields(tenant_id = %caller.tenant_id, job_name = %job.name))]
+    pub async fn create_job(&self, caller: &Caller, job: &Job) -> Result<(JobResponse, bool), ServiceError> {
+        info!("Service: Creating job with name={} for tenant={}", job.name, caller.tenant_id);
+
+        job.validate()
+            .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
 
-        // Create job in database
-        let job_row = JobRepository::create(&self.pool, job)
+        // Create job in database, remembering which tenant and trace enqueued it
+        let context = self.enqueue_context(caller);
+        let (job_row, created) = JobRepository::create(&self.pool, job, &context).await?;
+
+        if !created {
+            let message = if job.idempotency_key.is_some() && job_row.idempotency_key == job.idempotency_key {
+                "Job already created for this idempotency key"
+            } else {
+                "A job with this unique key is already pending"
+            };
+            info!("Service: {} (existing job id={})", message, job_row.id);
+            return Ok((
+                JobResponse {
+                    message: message.to_string(),
+                    job: job_row,
+                },
+                false,
+            ));
+        }
+
+        metrics().jobs_created.with_label_values(&[job_row.name.as_str()]).inc();
+
+        info!("Service: Job created successfully with id={}", job_row.id);
+
+        Ok((
+            JobResponse {
+                message: "Job created successfully".to_string(),
+                job: job_row,
+            },
+            true,
+        ))
+    }
+
+    /// Get the status history of a job
+    ///
+    /// # Returns
+    /// - `Ok(JobEventsResponse)` - Every recorded transition, oldest first
+    /// - `Err(ServiceError::NotFound)` - Job does not exist or belongs to another tenant
+    #[instrument(name = "JobService::get_job_events", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn get_job_events(&self, caller: &Caller, job_id: i32) -> Result<JobEventsResponse, ServiceError> {
+        JobRepository::find_by_id(&self.pool, &caller.tenant_id, job_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or(ServiceError::NotFound(job_id))?;
+
+        let events = JobRepository::list_events(&self.pool, &caller.tenant_id, job_id)
             .await
             .map_err(ServiceError::DatabaseError)?;
 
-        info!("Service: Job created successfully with id={}", job_row.id);
+        Ok(JobEventsResponse { job_id, events })
+    }
 
-        Ok(JobResponse {
-            message: "Job created successfully".to_string(),
-            job: job_row,
-        })
+    /// Open a live stream of the caller's job status transitions
+    ///
+    /// With `job_id`, only that job's transitions are streamed and the job must exist.
+    /// `last_event_id` replays the transitions recorded after it before going live.
+    ///
+    /// # Returns
+    /// - `Ok(stream)` - SSE body, ready for `HttpResponse::streaming`
+    /// - `Err(ServiceError::NotFound)` - Job does not exist or belongs to another tenant
+    #[instrument(name = "JobService::stream_events", skip(self, caller, broadcaster, query), fields(tenant_id = %caller.tenant_id))]
+    pub async fn stream_events(
+        &self,
+        caller: &Caller,
+        broadcaster: &JobEventBroadcaster,
+        job_id: Option<i32>,
+        query: EventStreamQuery,
+        last_event_id: Option<i64>,
+    ) -> Result<impl Stream<Item = Result<Bytes, actix_web::Error>>, ServiceError> {
+        if let Some(job_id) = job_id {
+            JobRepository::find_by_id(&self.pool, &caller.tenant_id, job_id)
+                .await
+                .map_err(ServiceError::DatabaseError)?
+                .ok_or(ServiceError::NotFound(job_id))?;
+        }
+
+        let filter = EventFilter {
+            tenant_id: caller.tenant_id.clone(),
+            job_id,
+            name: query.name,
+            status: query.status,
+        };
+
+        // Where to replay from if the client falls behind; read before subscribing so
+        // every event after it reaches the client live or by replay
+        let after_id = match last_event_id {
+            Some(id) => id,
+            None => JobRepository::latest_event_id(&self.pool)
+                .await
+                .map_err(ServiceError::DatabaseError)?,
+        };
+
+        info!("Service: Opening job event stream (resuming after {:?})", last_event_id);
+        Ok(event_stream(self.pool.clone(), broadcaster.subscribe(), filter, after_id, last_event_id.is_some()))
+    }
+
+    /// Transitions of the caller's `job_ids` recorded after `after_id`, oldest first
+    pub async fn list_events_for_jobs(
+        &self,
+        caller: &Caller,
+        job_ids: &[i32],
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<JobEventMessage>, ServiceError> {
+        JobRepository::list_events_for_jobs(&self.pool, &caller.tenant_id, job_ids, after_id, limit)
+            .await
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Bulk create jobs from uploaded file data
     ///
     /// # Business Logic
     /// - Validates each job individually
-    /// - Collects validation errors with job names
-    /// - Bulk inserts only valid jobs
-    /// - Returns summary with created count and errors
+    /// - Collects validation errors with job names and array indexes
+    /// - In atomic mode, inserts nothing if any job is invalid
+    /// - Bulk inserts only valid jobs under a new batch id, in one transaction
+    /// - Returns summary with created ids, the original ids of duplicates and errors
     ///
     /// # Returns
     /// - `Ok(BulkJobResponse)` - Jobs processed (may have partial errors)
     /// - `Err(ServiceError)` - Complete failure
-    pub async fn bulk_create_jobs(&self, jobs: Vec<Job>) -> Result<BulkJobResponse, ServiceError> {
+    #[instrument(name = "JobService::bulk_create_jobs", skip_all, fields(tenant_id = %caller.tenant_id, count = jobs.len()))]
+    pub async fn bulk_create_jobs(
+        &self,
+        caller: &Caller,
+        jobs: Vec<Job>,
+        mode: BulkMode,
+    ) -> Result<BulkJobResponse, ServiceError> {
         info!("Service: Processing bulk job creation for {} jobs", jobs.len());
 
+        let batch_id = Uuid::new_v4();
         let mut valid_jobs = Vec::new();
+        let mut valid_positions = Vec::new();
         let mut errors = Vec::new();
 
         // Validate each job
-        for job in jobs {
+        for (index, job) in jobs.into_iter().enumerate() {
             if let Err(validation_errors) = job.validate() {
-                let error_messages: Vec<String> = validation_errors
-                    .field_errors()
-                    .values()
-                    .flat_map(|errors| {
-                        errors.iter().map(|e| {
-                            e.message
-                                .as_ref()
-                                .map(|m| m.to_string())
-                                .unwrap_or_else(|| "Validation error".to_string())
-                        })
-                    })
-                    .collect();
+                warn!("Service: Validation failed for job: {}", job.name);
 
                 errors.push(JobError {
-                    name: job.name.clone(),
-                    errors: error_messages,
+                    name: job.name,
+                    position: Position::Index(index),
+                    errors: validation_messages(&validation_errors),
                 });
-
-                warn!("Service: Validation failed for job: {}", job.name);
             } else {
                 valid_jobs.push(job);
+                valid_positions.push(Position::Index(index));
             }
         }
 
-        // Bulk insert valid jobs
-        let created_count = if !valid_jobs.is_empty() {
-            info!("Service: Bulk inserting {} valid jobs", valid_jobs.len());
+        if mode == BulkMode::Atomic && !errors.is_empty() {
+            warn!("Service: Atomic bulk upload rejected with {} validation errors", errors.len());
+            return Ok(rejected_bulk_response(batch_id, errors));
+        }
 
-            JobRepository::bulk_create(&self.pool, &valid_jobs)
-                .await
-                .map_err(ServiceError::DatabaseError)? as usize
+        // Bulk insert valid jobs; rows with a previously used idempotency key are skipped
+        let (created, duplicates) = if !valid_jobs.is_empty() {
+            self.insert_valid_jobs(caller, batch_id, &valid_jobs, &valid_positions).await?
         } else {
             warn!("Service: No valid jobs to insert");
-            0
+            (Vec::new(), Vec::new())
         };
 
+        let created_count = created.len();
         let error_count = errors.len();
+        let duplicate_count = duplicates.len();
 
         if error_count == 0 {
             info!("Service: Bulk job creation completed successfully: {} jobs created", created_count);
@@ -165,12 +379,108 @@ impl JobService {
 
         Ok(BulkJobResponse {
             message: format!(
-                "Bulk job creation completed. {} created, {} failed",
+                "Bulk job creation completed. {} created, {} duplicates, {} failed",
                 created_count,
+                duplicate_count,
                 error_count
             ),
             created: created_count,
+            duplicates: duplicate_count,
+            batch_id,
+            jobs: created,
+            duplicate_jobs: duplicates,
             errors,
+            error: None,
+        })
+    }
+
+    /// Insert already validated jobs in one transaction
+    ///
+    /// `positions[i]` locates `jobs[i]` in the upload. Returns the created jobs
+    /// and the skipped duplicates with the ids of their original jobs.
+    pub(crate) async fn insert_valid_jobs(
+        &self,
+        caller: &Caller,
+        batch_id: Uuid,
+        jobs: &[Job],
+        positions: &[Position],
+    ) -> Result<(Vec<CreatedJob>, Vec<DuplicateJob>), ServiceError> {
+        info!("Service: Bulk inserting {} valid jobs", jobs.len());
+
+        let mut context = self.enqueue_context(caller);
+        context.batch_id = Some(batch_id);
+        let inserted = JobRepository::bulk_create(&self.pool, jobs, &context).await?;
+
+        let created = inserted
+            .created
+            .into_iter()
+            .map(|(index, id)| {
+                metrics().jobs_created.with_label_values(&[jobs[index].name.as_str()]).inc();
+                CreatedJob { id, position: positions[index] }
+            })
+            .collect();
+        let duplicates = inserted
+            .duplicates
+            .into_iter()
+            .map(|(index, id)| DuplicateJob { id, position: positions[index] })
+            .collect();
+
+        Ok((created, duplicates))
+    }
+
+    /// List the jobs of one bulk upload
+    #[instrument(name = "JobService::list_jobs_by_batch", skip_all, fields(tenant_id = %caller.tenant_id, %batch_id))]
+    pub async fn list_jobs_by_batch(&self, caller: &Caller, batch_id: Uuid) -> Result<JobListResponse, ServiceError> {
+        let jobs = JobRepository::list_by_batch(&self.pool, &caller.tenant_id, batch_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        Ok(JobListResponse { jobs })
+    }
+
+    fn enqueue_context(&self, caller: &Caller) -> EnqueueContext {
+        enqueue_context(caller, self.idempotency_retention_hours)
+    }
+}
+
+/// Metadata captured from the current request and stored with created jobs
+pub(crate) fn enqueue_context(caller: &Caller, idempotency_retention_hours: i32) -> EnqueueContext {
+    EnqueueContext {
+        tenant_id: caller.tenant_id.clone(),
+        trace_context: telemetry::current_trace_context(),
+        request_id: request_id::current(),
+        idempotency_retention_hours,
+        batch_id: None,
+        api_key_id: Some(caller.key_id),
+    }
+}
+
+/// Flatten validator errors into their messages
+pub(crate) fn validation_messages(validation_errors: &ValidationErrors) -> Vec<String> {
+    validation_errors
+        .field_errors()
+        .values()
+        .flat_map(|errors| {
+            errors.iter().map(|e| {
+                e.message
+                    .as_ref()
+                    .map(|m| m.to_string())
+                    .unwrap_or_else(|| "Validation error".to_string())
+            })
         })
+        .collect()
+}
+
+/// Summary of an atomic bulk upload that was rejected; nothing was created
+pub(crate) fn rejected_bulk_response(batch_id: Uuid, errors: Vec<JobError>) -> BulkJobResponse {
+    BulkJobResponse {
+        message: format!("Bulk job creation rejected. {} failed, nothing was created", errors.len()),
+        created: 0,
+        duplicates: 0,
+        batch_id,
+        jobs: Vec::new(),
+        duplicate_jobs: Vec::new(),
+        errors,
+        error: None,
     }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use actix_web::web::Data;
+use actix_ws::{Closed, Message, MessageStream, Session};
+use futures_util::StreamExt;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{debug, error, info, warn};
+
+use crate::api::auth::Caller;
+use crate::db::models::JobEventMessage;
+use super::dto::{SocketMessage, SocketRequest};
+use super::service::JobService;
+use super::stream::JobEventNotice;
+
+/// Most jobs one connection may be subscribed to at once
+const MAX_SUBSCRIPTIONS: usize = 1000;
+
+/// Statuses after which a job has no further transitions
+const TERMINAL_STATUSES: &[&str] = &["success", "failed", "cancelled"];
+
+/// Events read per query while catching up after falling behind
+const REPLAY_PAGE_SIZE: i64 = 500;
+
+/// Jobs a WebSocket connection created and is listening to
+#[derive(Default)]
+struct Connection {
+    submitted: HashSet<i32>,
+    subscribed: HashSet<i32>,
+    /// Highest event id received from the broadcast or replayed
+    last_id: i64,
+    /// Highest event id sent from a replay; live copies of those are skipped
+    replayed_up_to: i64,
+}
+
+/// Serve one WebSocket connection until either side closes it
+///
+/// Requests and events are handled one at a time and every reply waits for room in
+/// the session's bounded outgoing buffer, so a client that reads slowly stalls its
+/// own connection rather than growing server memory. While stalled, its broadcast
+/// receiver falls behind; the dropped events of subscribed jobs are then read from
+/// `job_events` and sent in order. Only if that fails is a `lagged` message sent.
+pub async fn run(
+    service: Data<JobService>,
+    caller: Caller,
+    mut session: Session,
+    mut messages: MessageStream,
+    mut events: broadcast::Receiver<Arc<JobEventNotice>>,
+) {
+    info!(tenant_id = %caller.tenant_id, "WebSocket connection opened");
+    let mut connection = Connection::default();
+
+    let reason = loop {
+        tokio::select! {
+            message = messages.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = connection.handle(&service, &caller, &text).await;
+                    if send(&mut session, &reply).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Binary(_))) => {
+                    let reply = SocketMessage::Error {
+                        reference: None,
+                        message: "Binary messages are not supported".to_string(),
+                    };
+                    if send(&mut session, &reply).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    warn!(error = %e, "WebSocket protocol error");
+                    break None;
+                }
+                None => break None,
+            },
+            event = events.recv() => match event {
+                // Bulk-created jobs were never submitted on a connection, so their
+                // initial events are of no interest here
+                Ok(notice) => if let JobEventNotice::Event(event) = notice.as_ref() {
+                    connection.last_id = connection.last_id.max(event.id);
+                    if event.id > connection.replayed_up_to && connection.forward(&mut session, event).await.is_err() {
+                        return;
+                    }
+                },
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, after_id = connection.last_id, "WebSocket client lagged behind job events");
+                    if connection.catch_up(&service, &caller, &mut session, skipped).await.is_err() {
+                        return;
+                    }
+                }
+                Err(RecvError::Closed) => break None,
+            },
+        }
+    };
+
+    let _ = session.close(reason).await;
+    info!(tenant_id = %caller.tenant_id, "WebSocket connection closed");
+}
+
+impl Connection {
+    /// Send `event` if its job is subscribed, ending the subscription at a terminal status
+    async fn forward(&mut self, session: &mut Session, event: &JobEventMessage) -> Result<(), Closed> {
+        if !self.subscribed.contains(&event.job_id) {
+            return Ok(());
+        }
+        if TERMINAL_STATUSES.contains(&event.to_status.as_str()) {
+            self.subscribed.remove(&event.job_id);
+        }
+        send(session, &SocketMessage::Event { event: event.clone() }).await
+    }
+
+    /// Send the events of subscribed jobs the broadcast dropped, read from `job_events`
+    async fn catch_up(
+        &mut self,
+        service: &JobService,
+        caller: &Caller,
+        session: &mut Session,
+        skipped: u64,
+    ) -> Result<(), Closed> {
+        loop {
+            if self.subscribed.is_empty() {
+                return Ok(());
+            }
+
+            let job_ids: Vec<i32> = self.subscribed.iter().copied().collect();
+            let page = match service.list_events_for_jobs(caller, &job_ids, self.last_id, REPLAY_PAGE_SIZE).await {
+                Ok(page) => page,
+                Err(e) => {
+                    error!(error = %e, "Failed to replay job events for WebSocket client");
+                    return send(session, &SocketMessage::Lagged { skipped }).await;
+                }
+            };
+
+            let complete = (page.len() as i64) < REPLAY_PAGE_SIZE;
+            for event in &page {
+                self.last_id = event.id;
+                self.replayed_up_to = event.id;
+                self.forward(session, event).await?;
+            }
+            if complete {
+                return Ok(());
+            }
+        }
+    }
+
+    async fn handle(&mut self, service: &JobService, caller: &Caller, text: &str) -> SocketMessage {
+        let request = match serde_json::from_str::<SocketRequest>(text) {
+            Ok(request) => request,
+            Err(e) => {
+                return SocketMessage::Error {
+                    reference: None,
+                    message: format!("Invalid message: {}", e),
+                }
+            }
+        };
+
+        match request {
+            SocketRequest::Submit { reference, job, subscribe } => {
+                match service.create_job(caller, &job).await {
+                    Ok((response, created)) => {
+                        let id = response.job.id;
+                        self.submitted.insert(id);
+                        if subscribe && self.subscribed.len() < MAX_SUBSCRIPTIONS {
+                            self.subscribed.insert(id);
+                        }
+                        debug!("Job {} submitted over WebSocket", id);
+                        SocketMessage::Submitted { reference, created, job: Box::new(response.job) }
+                    }
+                    Err(e) => SocketMessage::Error { reference, message: e.to_string() },
+                }
+            }
+            SocketRequest::Subscribe { ids } => {
+                if let Some(id) = ids.iter().find(|id| !self.submitted.contains(*id)) {
+                    return SocketMessage::Error {
+                        reference: None,
+                        message: format!("Job {} was not submitted on this connection", id),
+                    };
+                }
+
+                let new = ids.iter().filter(|id| !self.subscribed.contains(*id)).count();
+                if self.subscribed.len() + new > MAX_SUBSCRIPTIONS {
+                    return SocketMessage::Error {
+                        reference: None,
+                        message: format!("At most {} subscriptions per connection", MAX_SUBSCRIPTIONS),
+                    };
+                }
+
+                self.subscribed.extend(ids.iter().copied());
+                SocketMessage::Subscribed { ids }
+            }
+            SocketRequest::Unsubscribe { ids } => {
+                for id in &ids {
+                    self.subscribed.remove(id);
+                }
+                SocketMessage::Unsubscribed { ids }
+            }
+        }
+    }
+}
+
+async fn send(session: &mut Session, message: &SocketMessage) -> Result<(), Closed> {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    session.text(text).await
+}
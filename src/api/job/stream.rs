@@ -0,0 +1,221 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use actix_web::web::Bytes;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use sqlx::postgres::PgListener;
+use sqlx::{Pool, Postgres};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{sleep, timeout, Duration};
+use tracing::{error, info, warn};
+
+use crate::db::job_repository::JobRepository;
+use crate::db::models::JobEventMessage;
+
+/// Postgres channel the `notify_job_events` trigger publishes on
+const CHANNEL: &str = "job_events";
+
+/// Events buffered per subscriber before it is considered lagging
+const BROADCAST_CAPACITY: usize = 1024;
+
+/// Events read per query while replaying from `Last-Event-ID`
+const REPLAY_PAGE_SIZE: i64 = 500;
+
+/// Idle time after which a comment line is sent to keep proxies from closing the stream
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// A notification on the `job_events` channel
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobEventNotice {
+    /// One status transition
+    Event(JobEventMessage),
+    /// The initial events of a multi-row insert, published together; read them from `job_events`
+    Created { tenant_id: String },
+}
+
+/// Fans out job status transitions from Postgres LISTEN/NOTIFY to SSE clients
+///
+/// One listener connection per API process receives the events of every worker
+/// and every replica; each stream client subscribes to an in-process broadcast.
+pub struct JobEventBroadcaster {
+    sender: broadcast::Sender<Arc<JobEventNotice>>,
+}
+
+impl JobEventBroadcaster {
+    /// Start listening on the `job_events` channel in a background task
+    pub fn start(pool: Pool<Postgres>) -> Self {
+        let (sender, _) = broadcast::channel(BROADCAST_CAPACITY);
+        tokio::spawn(listen(pool, sender.clone()));
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<JobEventNotice>> {
+        self.sender.subscribe()
+    }
+}
+
+/// Forward notifications to the broadcast, reconnecting whenever the listener fails
+///
+/// Events published while disconnected are not broadcast; clients recover them by
+/// reconnecting with `Last-Event-ID`.
+async fn listen(pool: Pool<Postgres>, sender: broadcast::Sender<Arc<JobEventNotice>>) {
+    loop {
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(error = ?e, "Failed to connect job event listener");
+                sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+
+        if let Err(e) = listener.listen(CHANNEL).await {
+            error!(error = ?e, "Failed to LISTEN on {}", CHANNEL);
+            sleep(Duration::from_secs(5)).await;
+            continue;
+        }
+
+        info!("Listening for job events on channel {}", CHANNEL);
+
+        loop {
+            match listener.recv().await {
+                Ok(notification) => match serde_json::from_str::<JobEventNotice>(notification.payload()) {
+                    // Sending only fails when nobody is subscribed
+                    Ok(notice) => {
+                        let _ = sender.send(Arc::new(notice));
+                    }
+                    Err(e) => warn!(error = %e, "Ignoring malformed job event notification"),
+                },
+                Err(e) => {
+                    error!(error = ?e, "Job event listener failed; reconnecting");
+                    break;
+                }
+            }
+        }
+
+        sleep(Duration::from_secs(1)).await;
+    }
+}
+
+/// Which events a stream client receives
+#[derive(Debug, Clone)]
+pub struct EventFilter {
+    pub tenant_id: String,
+    pub job_id: Option<i32>,
+    pub name: Option<String>,
+    pub status: Option<String>,
+}
+
+impl EventFilter {
+    fn matches(&self, event: &JobEventMessage) -> bool {
+        event.tenant_id == self.tenant_id
+            && self.job_id.is_none_or(|id| event.job_id == id)
+            && self.name.as_deref().is_none_or(|name| event.name == name)
+            && self.status.as_deref().is_none_or(|status| event.to_status == status)
+    }
+}
+
+struct StreamState {
+    pool: Pool<Postgres>,
+    receiver: broadcast::Receiver<Arc<JobEventNotice>>,
+    filter: EventFilter,
+    replay: VecDeque<JobEventMessage>,
+    replaying: bool,
+    /// Highest event id sent or passed over; replays continue after it
+    last_id: i64,
+    /// Highest event id sent from a replay; live copies of those are skipped
+    replayed_up_to: i64,
+}
+
+/// SSE body of matching job events
+///
+/// With `replay`, events recorded after `after_id` are replayed from `job_events`
+/// first. The receiver is subscribed before the replay starts, so nothing committed
+/// in between is missed. Events the client cannot get from the live broadcast, because
+/// it fell behind or they were published as one bulk notification, are replayed the
+/// same way from the last event id it passed.
+pub fn event_stream(
+    pool: Pool<Postgres>,
+    receiver: broadcast::Receiver<Arc<JobEventNotice>>,
+    filter: EventFilter,
+    after_id: i64,
+    replay: bool,
+) -> impl Stream<Item = Result<Bytes, actix_web::Error>> {
+    let state = StreamState {
+        pool,
+        receiver,
+        filter,
+        replay: VecDeque::new(),
+        replaying: replay,
+        last_id: after_id,
+        // Without a replay every broadcast event is new to the client
+        replayed_up_to: if replay { after_id } else { 0 },
+    };
+
+    // Ask clients to reconnect after 3 seconds, then stream events
+    let retry = stream::once(async { Ok(Bytes::from_static(b"retry: 3000\n\n")) });
+    retry.chain(stream::unfold(state, next_frame))
+}
+
+async fn next_frame(mut state: StreamState) -> Option<(Result<Bytes, actix_web::Error>, StreamState)> {
+    loop {
+        if let Some(event) = state.replay.pop_front() {
+            state.replayed_up_to = event.id;
+            state.last_id = event.id;
+            return Some((Ok(frame(&event)), state));
+        }
+
+        if state.replaying {
+            let page = JobRepository::list_events_after(
+                &state.pool,
+                &state.filter.tenant_id,
+                state.last_id,
+                state.filter.job_id,
+                state.filter.name.as_deref(),
+                state.filter.status.as_deref(),
+                REPLAY_PAGE_SIZE,
+            )
+            .await;
+
+            match page {
+                Ok(page) => {
+                    state.replaying = page.len() as i64 == REPLAY_PAGE_SIZE;
+                    state.replay.extend(page);
+                    continue;
+                }
+                Err(e) => {
+                    error!(error = ?e, "Failed to replay job events; closing stream");
+                    return None;
+                }
+            }
+        }
+
+        match timeout(KEEP_ALIVE, state.receiver.recv()).await {
+            Err(_) => return Some((Ok(Bytes::from_static(b": keep-alive\n\n")), state)),
+            Ok(Ok(notice)) => match notice.as_ref() {
+                JobEventNotice::Event(event) => {
+                    state.last_id = state.last_id.max(event.id);
+                    if event.id > state.replayed_up_to && state.filter.matches(event) {
+                        return Some((Ok(frame(event)), state));
+                    }
+                }
+                JobEventNotice::Created { tenant_id } => {
+                    state.replaying |= *tenant_id == state.filter.tenant_id;
+                }
+            },
+            Ok(Err(RecvError::Lagged(skipped))) => {
+                warn!(skipped, after_id = state.last_id, "Event stream client lagged; replaying the skipped events");
+                state.replaying = true;
+            }
+            Ok(Err(RecvError::Closed)) => return None,
+        }
+    }
+}
+
+/// One SSE message; the event id is the `job_events` id
+fn frame(event: &JobEventMessage) -> Bytes {
+    let data = serde_json::to_string(event).unwrap_or_default();
+    Bytes::from(format!("id: {}\nevent: status\ndata: {}\n\n", event.id, data))
+}
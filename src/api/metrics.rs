@@ -0,0 +1,52 @@
+use actix_web::{HttpResponse, Responder, get, middleware::from_fn, web};
+use sqlx::{Pool, Postgres};
+use tokio::sync::Semaphore;
+use tracing::error;
+
+use crate::api::auth::require_metrics_read;
+use crate::db::job_repository::JobRepository;
+use crate::metrics::metrics;
+
+/// Prometheus metrics endpoint
+///
+/// Refreshes the point-in-time gauges (semaphore, pool, queue depth) and
+/// returns every metric in the Prometheus text format. Requires the `metrics:read`
+/// scope, so a scraper does not need an admin key.
+#[get("/metrics", wrap = "from_fn(require_metrics_read)")]
+async fn prometheus_metrics(
+    pool: web::Data<Pool<Postgres>>,
+    semaphore: web::Data<Semaphore>,
+) -> impl Responder {
+    let metrics = metrics();
+
+    metrics
+        .semaphore_permits_available
+        .set(semaphore.available_permits() as i64);
+    metrics.db_pool_size.set(pool.size() as i64);
+    metrics.db_pool_idle.set(pool.num_idle() as i64);
+
+    match JobRepository::count_by_status(pool.get_ref()).await {
+        Ok(counts) => {
+            metrics.queue_depth.reset();
+            for (status, count) in counts {
+                metrics.queue_depth.with_label_values(&[status.as_str()]).set(count);
+            }
+        }
+        // Keep serving the other metrics; queue depth keeps its last value
+        Err(e) => error!("Failed to refresh queue depth metrics: {:?}", e),
+    }
+
+    match metrics.encode() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("text/plain; version=0.0.4")
+            .body(body),
+        Err(e) => {
+            error!("Failed to encode metrics: {:?}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+pub fn metrics_config(config: &mut web::ServiceConfig) {
+    config.service(prometheus_metrics);
+}
@@ -1,5 +1,13 @@
 pub mod dummy;
 pub mod state;
 pub mod job;
+pub mod batch;
+pub mod workflow;
 pub mod validation;
-pub mod health;
\ No newline at end of file
+pub mod health;
+pub mod metrics;
+pub mod stats;
+pub mod workers;
+pub mod queues;
+pub mod request_id;
+pub mod auth;
\ No newline at end of file
@@ -0,0 +1,16 @@
+use serde::Serialize;
+use crate::db::models::PausedQueueRow;
+
+/// Response for pausing or resuming a queue
+#[derive(Serialize)]
+pub struct QueueResponse {
+    pub message: String,
+    pub name: String,
+    pub paused: Option<PausedQueueRow>,
+}
+
+/// Response for the paused queue listing
+#[derive(Serialize)]
+pub struct PausedQueuesResponse {
+    pub paused: Vec<PausedQueueRow>,
+}
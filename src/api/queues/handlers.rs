@@ -0,0 +1,54 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get, post,
+    middleware::from_fn,
+    web::{Data, Json, Path, ServiceConfig, scope},
+};
+use crate::api::auth::{Caller, require_admin};
+use super::models::PauseQueue;
+use super::service::QueueService;
+
+/// List the caller's paused queues
+#[get("", wrap = "from_fn(require_admin)")]
+async fn list_paused_queues(service: Data<QueueService>, caller: Caller) -> impl Responder {
+    match service.list_paused(&caller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Stop every worker from acquiring the caller's jobs named `{name}`; submissions still succeed
+#[post("/{name}/pause", wrap = "from_fn(require_admin)")]
+async fn pause_queue(
+    service: Data<QueueService>,
+    caller: Caller,
+    path: Path<String>,
+    body: Option<Json<PauseQueue>>,
+) -> impl Responder {
+    let reason = body.and_then(|body| body.into_inner().reason);
+    match service.pause(&caller, path.into_inner(), reason.as_deref()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+/// Let workers acquire the caller's jobs named `{name}` again
+#[post("/{name}/resume", wrap = "from_fn(require_admin)")]
+async fn resume_queue(
+    service: Data<QueueService>,
+    caller: Caller,
+    path: Path<String>,
+) -> impl Responder {
+    match service.resume(&caller, path.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn queues_config(config: &mut ServiceConfig) {
+    config.service(
+        scope("queues")
+            .service(list_paused_queues)
+            .service(pause_queue)
+            .service(resume_queue)
+    );
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+/// Optional body of a pause request
+#[derive(Deserialize)]
+pub struct PauseQueue {
+    pub reason: Option<String>,
+}
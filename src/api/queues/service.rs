@@ -0,0 +1,82 @@
+use sqlx::{Pool, Postgres};
+use tracing::{info, instrument};
+
+use crate::api::auth::Caller;
+use crate::api::job::service::ServiceError;
+use crate::db::queue_repository::QueueRepository;
+use super::dto::{PausedQueuesResponse, QueueResponse};
+
+/// Longest queue name that can be paused
+const MAX_QUEUE_NAME_LEN: usize = 255;
+
+/// Queue service containing business logic
+pub struct QueueService {
+    pool: Pool<Postgres>,
+}
+
+impl QueueService {
+    /// Create a new QueueService instance
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// List the caller's paused queues
+    #[instrument(name = "QueueService::list_paused", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn list_paused(&self, caller: &Caller) -> Result<PausedQueuesResponse, ServiceError> {
+        let paused = QueueRepository::list_paused(&self.pool, &caller.tenant_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        Ok(PausedQueuesResponse { paused })
+    }
+
+    /// Stop workers from acquiring the caller's jobs named `name`; pausing again updates the reason
+    ///
+    /// Other tenants' jobs of the same name are not affected.
+    ///
+    /// # Returns
+    /// - `Ok(QueueResponse)` - The queue is paused
+    /// - `Err(ServiceError::ValidationError)` - The name is empty or too long
+    #[instrument(name = "QueueService::pause", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn pause(&self, caller: &Caller, name: String, reason: Option<&str>) -> Result<QueueResponse, ServiceError> {
+        if name.is_empty() || name.len() > MAX_QUEUE_NAME_LEN {
+            return Err(ServiceError::ValidationError(format!(
+                "Queue name must be 1-{} characters",
+                MAX_QUEUE_NAME_LEN
+            )));
+        }
+
+        let paused = QueueRepository::pause(&self.pool, &caller.tenant_id, &name, &caller.name, reason)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        info!("Service: Queue {} of tenant {} paused by {}", name, caller.tenant_id, caller.name);
+        Ok(QueueResponse {
+            message: "Queue paused".to_string(),
+            name,
+            paused: Some(paused),
+        })
+    }
+
+    /// Let workers acquire the caller's jobs named `name` again
+    ///
+    /// # Returns
+    /// - `Ok(QueueResponse)` - The queue was paused and is now resumed
+    /// - `Err(ServiceError::QueueNotPaused)` - The caller's queue was not paused
+    #[instrument(name = "QueueService::resume", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn resume(&self, caller: &Caller, name: String) -> Result<QueueResponse, ServiceError> {
+        let resumed = QueueRepository::resume(&self.pool, &caller.tenant_id, &name)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+        if !resumed {
+            return Err(ServiceError::QueueNotPaused(name));
+        }
+
+        info!("Service: Queue {} of tenant {} resumed", name, caller.tenant_id);
+        Ok(QueueResponse {
+            message: "Queue resumed".to_string(),
+            name,
+            paused: None,
+        })
+    }
+}
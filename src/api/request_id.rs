@@ -0,0 +1,90 @@
+use actix_web::{
+    body::MessageBody,
+    dev::{ServiceRequest, ServiceResponse},
+    error::InternalError,
+    http::header::{HeaderMap, HeaderName, HeaderValue},
+    middleware::Next,
+    Error,
+};
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+/// Header used to accept and return the request id
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request id we accept before generating our own
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    /// Request id of the request being handled on the current task
+    static REQUEST_ID: String;
+}
+
+/// Request id of the request currently being handled, if any
+///
+/// Available anywhere inside handler execution (extractors, services, error responses).
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run `future` with `id` as the current request id
+///
+/// For work that outlives the request's task, such as a WebSocket connection.
+pub async fn scope<F: std::future::Future>(id: String, future: F) -> F::Output {
+    REQUEST_ID.scope(id, future).await
+}
+
+/// Accept a client-supplied id only if it is short and header/log safe
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Attach the request id header to a response
+fn insert_header(headers: &mut HeaderMap, request_id: &str) {
+    // Validated or generated, so always a valid header value
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        headers.insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+}
+
+/// Middleware that accepts or generates an `X-Request-Id` for every request
+///
+/// - Reuses a valid incoming `X-Request-Id`, otherwise generates a UUID v4
+/// - Runs the rest of the request inside a `request` span carrying `request_id`
+/// - Makes the id available via `current()` for services and `ErrorResponse`
+/// - Returns the id in the `X-Request-Id` response header, including on errors
+pub async fn request_id_middleware<B: MessageBody>(
+    req: ServiceRequest,
+    next: Next<B>,
+) -> Result<ServiceResponse<B>, Error> {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid(id))
+        .map(String::from)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = info_span!("request", request_id = %request_id);
+
+    let result = REQUEST_ID
+        .scope(request_id.clone(), next.call(req))
+        .instrument(span)
+        .await;
+
+    match result {
+        Ok(mut response) => {
+            insert_header(response.headers_mut(), &request_id);
+            Ok(response)
+        }
+        // Render errors here so they carry the header as well. The request is not
+        // cloned up front: routing needs sole ownership of it further down.
+        Err(err) => {
+            let mut response = err.error_response();
+            insert_header(response.headers_mut(), &request_id);
+            Err(InternalError::from_response(err, response).into())
+        }
+    }
+}
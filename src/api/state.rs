@@ -1,4 +1,6 @@
-use actix_web::{HttpResponse, Responder, get, web};
+use actix_web::{HttpResponse, Responder, get, middleware::from_fn, web};
+
+use crate::api::auth::require_jobs_read;
 
 pub struct AppState {
     app_name: String,
@@ -24,6 +26,6 @@ fn get_scope() -> actix_web::Scope {
 }
 
 pub fn state_config(config: &mut web::ServiceConfig) {
-    let scope = get_scope();
+    let scope = get_scope().wrap(from_fn(require_jobs_read));
     config.service(scope);
 }
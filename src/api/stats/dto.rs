@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// Jobs finished in a time window, by outcome
+#[derive(Serialize)]
+pub struct FinishedCounts {
+    pub succeeded: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+}
+
+#[derive(Serialize)]
+pub struct Throughput {
+    pub last_minute: FinishedCounts,
+    pub last_hour: FinishedCounts,
+}
+
+/// Response for queue statistics
+#[derive(Serialize)]
+pub struct StatsResponse {
+    pub by_status: BTreeMap<String, i64>,
+    /// Job name to its counts by status
+    pub by_name: BTreeMap<String, BTreeMap<String, i64>>,
+    /// Wait of the oldest `new` job; `null` when nothing is queued
+    pub oldest_pending_age_secs: Option<f64>,
+    pub throughput: Throughput,
+    /// Average of the final attempt's run time over jobs finished in the last hour
+    pub avg_run_time_ms: Option<f64>,
+}
@@ -0,0 +1,23 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get,
+    middleware::from_fn,
+    web::{Data, ServiceConfig},
+};
+use crate::api::auth::{Caller, require_jobs_read};
+use super::service::StatsService;
+
+/// Queue statistics for the caller's tenant
+#[get("/stats", wrap = "from_fn(require_jobs_read)")]
+async fn get_stats(
+    service: Data<StatsService>,
+    caller: Caller,
+) -> impl Responder {
+    match service.get_stats(&caller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn stats_config(config: &mut ServiceConfig) {
+    config.service(get_stats);
+}
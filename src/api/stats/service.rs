@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use sqlx::{Pool, Postgres};
+use tracing::instrument;
+
+use crate::api::auth::Caller;
+use crate::api::job::service::ServiceError;
+use crate::db::stats_repository::StatsRepository;
+use super::dto::{FinishedCounts, StatsResponse, Throughput};
+
+/// Stats service containing business logic
+pub struct StatsService {
+    pool: Pool<Postgres>,
+}
+
+impl StatsService {
+    /// Create a new StatsService instance
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// Queue statistics for the caller's tenant
+    ///
+    /// Counts come from the trigger-maintained `job_counts` table and the rest from
+    /// index ranges, so this stays fast however many jobs are stored.
+    #[instrument(name = "StatsService::get_stats", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn get_stats(&self, caller: &Caller) -> Result<StatsResponse, ServiceError> {
+        let tenant_id = caller.tenant_id.as_str();
+
+        let (counts, oldest_pending_age_secs, recent) = tokio::try_join!(
+            StatsRepository::count_jobs(&self.pool, tenant_id),
+            StatsRepository::oldest_pending_age_secs(&self.pool, tenant_id),
+            StatsRepository::recent_jobs(&self.pool, tenant_id),
+        )
+        .map_err(ServiceError::DatabaseError)?;
+
+        let mut by_status = BTreeMap::new();
+        let mut by_name: BTreeMap<String, BTreeMap<String, i64>> = BTreeMap::new();
+        for row in counts {
+            *by_status.entry(row.status.clone()).or_insert(0) += row.count;
+            by_name.entry(row.name).or_default().insert(row.status, row.count);
+        }
+
+        Ok(StatsResponse {
+            by_status,
+            by_name,
+            oldest_pending_age_secs,
+            throughput: Throughput {
+                last_minute: FinishedCounts {
+                    succeeded: recent.succeeded_last_minute,
+                    failed: recent.failed_last_minute,
+                    cancelled: recent.cancelled_last_minute,
+                },
+                last_hour: FinishedCounts {
+                    succeeded: recent.succeeded_last_hour,
+                    failed: recent.failed_last_hour,
+                    cancelled: recent.cancelled_last_hour,
+                },
+            },
+            avg_run_time_ms: recent.avg_run_time_ms,
+        })
+    }
+}
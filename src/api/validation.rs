@@ -1,10 +1,24 @@
 use actix_web::HttpResponse;
 use serde::Serialize;
+use crate::api::request_id;
 
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub error: String,
     pub fields: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+    /// Build an error response tagged with the current request id
+    pub fn new(error: impl Into<String>, fields: serde_json::Value) -> Self {
+        Self {
+            error: error.into(),
+            fields,
+            request_id: request_id::current(),
+        }
+    }
 }
 
 /// Creates a configured JsonConfig with standardized error handling for the entire project
@@ -31,10 +45,10 @@ pub fn json_config() -> actix_web_validator::JsonConfig {
                         );
                     }
 
-                    let error_response = ErrorResponse {
-                        error: "Validation failed".to_string(),
-                        fields: serde_json::Value::Object(fields),
-                    };
+                    let error_response = ErrorResponse::new(
+                        "Validation failed",
+                        serde_json::Value::Object(fields),
+                    );
                     actix_web::error::InternalError::from_response(
                         "",
                         HttpResponse::BadRequest().json(error_response)
@@ -61,10 +75,10 @@ pub fn json_config() -> actix_web_validator::JsonConfig {
                         );
                     }
 
-                    let error_response = ErrorResponse {
-                        error: "Request validation failed".to_string(),
-                        fields: serde_json::Value::Object(fields),
-                    };
+                    let error_response = ErrorResponse::new(
+                        "Request validation failed",
+                        serde_json::Value::Object(fields),
+                    );
                     actix_web::error::InternalError::from_response(
                         "",
                         HttpResponse::BadRequest().json(error_response)
@@ -76,10 +90,10 @@ pub fn json_config() -> actix_web_validator::JsonConfig {
                         serde_json::json!("Validation error")
                     );
 
-                    let error_response = ErrorResponse {
-                        error: "Validation failed".to_string(),
-                        fields: serde_json::Value::Object(fields),
-                    };
+                    let error_response = ErrorResponse::new(
+                        "Validation failed",
+                        serde_json::Value::Object(fields),
+                    );
                     actix_web::error::InternalError::from_response(
                         "",
                         HttpResponse::BadRequest().json(error_response)
@@ -0,0 +1,8 @@
+use serde::Serialize;
+use crate::db::models::WorkerRow;
+
+/// Response for the worker listing
+#[derive(Serialize)]
+pub struct WorkersResponse {
+    pub workers: Vec<WorkerRow>,
+}
@@ -0,0 +1,20 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get,
+    middleware::from_fn,
+    web::{Data, ServiceConfig},
+};
+use crate::api::auth::{Caller, require_admin};
+use super::service::WorkerService;
+
+/// Registered workers across all processes, with their running jobs
+#[get("/workers", wrap = "from_fn(require_admin)")]
+async fn list_workers(service: Data<WorkerService>, caller: Caller) -> impl Responder {
+    match service.list_workers(&caller).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn workers_config(config: &mut ServiceConfig) {
+    config.service(list_workers);
+}
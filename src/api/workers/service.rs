@@ -0,0 +1,33 @@
+use sqlx::{Pool, Postgres};
+use tracing::instrument;
+
+use crate::api::auth::Caller;
+use crate::api::job::service::ServiceError;
+use crate::db::worker_repository::WorkerRepository;
+use super::dto::WorkersResponse;
+
+/// Worker service containing business logic
+pub struct WorkerService {
+    pool: Pool<Postgres>,
+}
+
+impl WorkerService {
+    /// Create a new WorkerService instance
+    pub fn new(pool: Pool<Postgres>) -> Self {
+        Self { pool }
+    }
+
+    /// List registered workers with the caller's jobs they are running
+    ///
+    /// Workers are shared by every tenant, but other tenants' jobs are left out.
+    /// Workers without a heartbeat for `WORKER_STALE_SECS` have already been removed;
+    /// `heartbeat_age_secs` shows how fresh the rest are.
+    #[instrument(name = "WorkerService::list_workers", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn list_workers(&self, caller: &Caller) -> Result<WorkersResponse, ServiceError> {
+        let workers = WorkerRepository::list(&self.pool, &caller.tenant_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        Ok(WorkersResponse { workers })
+    }
+}
@@ -0,0 +1,33 @@
+use serde::Serialize;
+use crate::db::models::{WorkflowRow, WorkflowStepRow};
+
+/// A step of a new workflow and the job it became
+#[derive(Serialize)]
+pub struct CreatedStep {
+    pub step: String,
+    pub job_id: i32,
+}
+
+/// Response for workflow creation
+#[derive(Serialize)]
+pub struct WorkflowResponse {
+    pub message: String,
+    pub workflow: WorkflowRow,
+    pub steps: Vec<CreatedStep>,
+}
+
+/// Response for a workflow's step-by-step state
+#[derive(Serialize)]
+pub struct WorkflowStatusResponse {
+    pub workflow: WorkflowRow,
+    pub steps: Vec<WorkflowStepRow>,
+}
+
+/// Response for cancelling a workflow
+#[derive(Serialize)]
+pub struct CancelWorkflowResponse {
+    pub message: String,
+    /// Steps that had not started yet; running steps finish normally
+    pub cancelled: u64,
+    pub workflow: WorkflowRow,
+}
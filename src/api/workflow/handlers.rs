@@ -0,0 +1,55 @@
+use actix_web::{
+    HttpResponse, Responder, ResponseError, get, post,
+    middleware::from_fn,
+    web::{Data, Path, ServiceConfig, scope},
+};
+use actix_web_validator::Json;
+use uuid::Uuid;
+use crate::api::auth::{Caller, require_admin, require_jobs_read, require_jobs_write};
+use super::models::CreateWorkflow;
+use super::service::WorkflowService;
+
+#[post("", wrap = "from_fn(require_jobs_write)")]
+async fn create_workflow(
+    service: Data<WorkflowService>,
+    caller: Caller,
+    workflow: Json<CreateWorkflow>,
+) -> impl Responder {
+    match service.create_workflow(&caller, workflow.into_inner()).await {
+        Ok(response) => HttpResponse::Created().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[get("/{id}", wrap = "from_fn(require_jobs_read)")]
+async fn get_workflow(
+    service: Data<WorkflowService>,
+    caller: Caller,
+    path: Path<Uuid>,
+) -> impl Responder {
+    match service.get_workflow(&caller, path.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+#[post("/{id}/cancel", wrap = "from_fn(require_admin)")]
+async fn cancel_workflow(
+    service: Data<WorkflowService>,
+    caller: Caller,
+    path: Path<Uuid>,
+) -> impl Responder {
+    match service.cancel_workflow(&caller, path.into_inner()).await {
+        Ok(response) => HttpResponse::Ok().json(response),
+        Err(e) => e.error_response(),
+    }
+}
+
+pub fn workflow_config(config: &mut ServiceConfig) {
+    config.service(
+        scope("workflows")
+            .service(create_workflow)
+            .service(get_workflow)
+            .service(cancel_workflow)
+    );
+}
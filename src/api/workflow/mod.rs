@@ -0,0 +1,8 @@
+pub mod models;
+pub mod dto;
+pub mod handlers;
+pub mod service;
+
+// Re-export commonly used types
+pub use models::CreateWorkflow;
+pub use service::WorkflowService;
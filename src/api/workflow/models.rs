@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+use crate::api::job::models::ParentFailure;
+
+/// How often a failed step is attempted again
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Validate)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    #[validate(range(min = 1, max = 20, message = "max_attempts must be between 1 and 20"))]
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for every further retry
+    #[serde(default)]
+    #[validate(range(max = 86400, message = "backoff_secs must be at most 86400"))]
+    pub backoff_secs: u32,
+}
+
+/// One step of a workflow, expanded into a single job
+#[derive(Serialize, Deserialize, Debug, Validate)]
+pub struct WorkflowStep {
+    /// Identifies the step within the workflow and in `edges`
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "Step id must be between 1 and 64 characters"
+    ))]
+    pub id: String,
+    #[validate(length(
+        min = 3,
+        max = 10,
+        message = "Name must be between 3 and 10 characters"
+    ))]
+    pub name: String,
+    #[serde(default)]
+    pub payload: Option<serde_json::Value>,
+    #[serde(default)]
+    #[validate(nested)]
+    pub retry: Option<RetryPolicy>,
+    #[serde(default)]
+    pub on_parent_failure: ParentFailure,
+}
+
+/// `to` runs after `from`
+#[derive(Deserialize, Debug)]
+pub struct WorkflowEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// Workflow spec submitted as a single document
+#[derive(Deserialize, Debug, Validate)]
+#[validate(schema(function = "validate_workflow"))]
+pub struct CreateWorkflow {
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Workflow name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+    #[validate(length(min = 1, max = 100, message = "A workflow needs between 1 and 100 steps"), nested)]
+    pub steps: Vec<WorkflowStep>,
+    #[serde(default)]
+    pub edges: Vec<WorkflowEdge>,
+}
+
+impl CreateWorkflow {
+    /// Indices of `steps` ordered so every step comes after the steps it depends on
+    ///
+    /// # Returns
+    /// - `Ok(order)` - Every step exactly once
+    /// - `Err(step)` - The edges form a cycle through `step`
+    pub fn topological_order(&self) -> Result<Vec<usize>, String> {
+        let index: HashMap<&str, usize> = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| (step.id.as_str(), i))
+            .collect();
+
+        let mut in_degree = vec![0usize; self.steps.len()];
+        let mut children = vec![Vec::new(); self.steps.len()];
+        for (from, to) in self.unique_edges() {
+            let (Some(&from), Some(&to)) = (index.get(from), index.get(to)) else {
+                continue;
+            };
+            children[from].push(to);
+            in_degree[to] += 1;
+        }
+
+        // Kahn's algorithm; start from the roots in submission order
+        let mut ready: Vec<usize> = (0..self.steps.len()).rev().filter(|&i| in_degree[i] == 0).collect();
+        let mut order = Vec::with_capacity(self.steps.len());
+        while let Some(step) = ready.pop() {
+            order.push(step);
+            for &child in &children[step] {
+                in_degree[child] -= 1;
+                if in_degree[child] == 0 {
+                    ready.push(child);
+                }
+            }
+        }
+
+        if order.len() < self.steps.len() {
+            let stuck = (0..self.steps.len()).find(|&i| in_degree[i] > 0).unwrap_or_default();
+            return Err(self.steps[stuck].id.clone());
+        }
+
+        Ok(order)
+    }
+
+    /// Step ids that `step` depends on
+    pub fn parents_of<'a>(&'a self, step: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.unique_edges()
+            .into_iter()
+            .filter(move |(_, to)| *to == step)
+            .map(|(from, _)| from)
+    }
+
+    fn unique_edges(&self) -> Vec<(&str, &str)> {
+        let mut seen = HashSet::new();
+        self.edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .filter(|edge| seen.insert(*edge))
+            .collect()
+    }
+}
+
+/// Step ids are unique, edges connect known steps, and the graph is acyclic
+fn validate_workflow(workflow: &CreateWorkflow) -> Result<(), ValidationError> {
+    let mut ids = HashSet::new();
+    for step in &workflow.steps {
+        if !ids.insert(step.id.as_str()) {
+            let mut error = ValidationError::new("steps");
+            error.message = Some(format!("Duplicate step id: {}", step.id).into());
+            return Err(error);
+        }
+    }
+
+    for edge in &workflow.edges {
+        if !ids.contains(edge.from.as_str()) || !ids.contains(edge.to.as_str()) {
+            let mut error = ValidationError::new("edges");
+            error.message = Some(format!("Edge {} -> {} references an unknown step", edge.from, edge.to).into());
+            return Err(error);
+        }
+    }
+
+    if let Err(step) = workflow.topological_order() {
+        let mut error = ValidationError::new("edges");
+        error.message = Some(format!("Edges form a cycle through step {}", step).into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(steps: &[&str], edges: &[(&str, &str)]) -> CreateWorkflow {
+        CreateWorkflow {
+            name: "nightly".to_string(),
+            steps: steps
+                .iter()
+                .map(|id| WorkflowStep {
+                    id: id.to_string(),
+                    name: "step".to_string(),
+                    payload: None,
+                    retry: None,
+                    on_parent_failure: ParentFailure::default(),
+                })
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| WorkflowEdge { from: from.to_string(), to: to.to_string() })
+                .collect(),
+        }
+    }
+
+    fn ids(workflow: &CreateWorkflow, order: Vec<usize>) -> Vec<&str> {
+        order.into_iter().map(|i| workflow.steps[i].id.as_str()).collect()
+    }
+
+    fn error_message(workflow: &CreateWorkflow) -> String {
+        let error = validate_workflow(workflow).unwrap_err();
+        error.message.unwrap().to_string()
+    }
+
+    #[test]
+    fn topological_order_puts_parents_first() {
+        let workflow = workflow(
+            &["report", "extract", "load", "notify"],
+            &[("extract", "load"), ("load", "report"), ("extract", "load"), ("report", "notify")],
+        );
+
+        let order = workflow.topological_order().unwrap();
+
+        assert_eq!(ids(&workflow, order), vec!["extract", "load", "report", "notify"]);
+    }
+
+    #[test]
+    fn topological_order_keeps_independent_steps_in_submission_order() {
+        let workflow = workflow(&["a", "b", "c"], &[]);
+
+        assert_eq!(workflow.topological_order().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn topological_order_reports_a_step_on_the_cycle() {
+        let workflow = workflow(&["root", "a", "b"], &[("root", "a"), ("a", "b"), ("b", "a")]);
+
+        assert_eq!(workflow.topological_order().unwrap_err(), "a");
+        assert_eq!(error_message(&workflow), "Edges form a cycle through step a");
+    }
+
+    #[test]
+    fn validate_workflow_rejects_self_edges() {
+        let workflow = workflow(&["a"], &[("a", "a")]);
+
+        assert_eq!(error_message(&workflow), "Edges form a cycle through step a");
+    }
+
+    #[test]
+    fn validate_workflow_rejects_duplicate_ids_and_unknown_steps() {
+        assert_eq!(error_message(&workflow(&["a", "a"], &[])), "Duplicate step id: a");
+        assert_eq!(
+            error_message(&workflow(&["a"], &[("a", "missing")])),
+            "Edge a -> missing references an unknown step"
+        );
+        assert!(validate_workflow(&workflow(&["a", "b"], &[("a", "b")])).is_ok());
+    }
+}
@@ -0,0 +1,101 @@
+use sqlx::{Pool, Postgres};
+use tracing::{info, instrument};
+use uuid::Uuid;
+
+use crate::api::auth::Caller;
+use crate::api::job::service::{enqueue_context, ServiceError};
+use crate::db::workflow_repository::WorkflowRepository;
+use crate::metrics::metrics;
+use super::dto::{CancelWorkflowResponse, CreatedStep, WorkflowResponse, WorkflowStatusResponse};
+use super::models::CreateWorkflow;
+
+/// Workflow service containing business logic
+pub struct WorkflowService {
+    pool: Pool<Postgres>,
+    idempotency_retention_hours: i32,
+}
+
+impl WorkflowService {
+    /// Create a new WorkflowService instance
+    pub fn new(pool: Pool<Postgres>, idempotency_retention_hours: i32) -> Self {
+        Self { pool, idempotency_retention_hours }
+    }
+
+    /// Expand a workflow spec into jobs
+    ///
+    /// # Business Logic
+    /// - The spec was validated by the extractor (unique step ids, known edges, no cycles)
+    /// - Inserts one job per step, parents first, and links them via job dependencies
+    /// - Steps with parents start `blocked` and are released as their parents finish
+    ///
+    /// # Returns
+    /// - `Ok(WorkflowResponse)` - Workflow created with the job id of every step
+    /// - `Err(ServiceError)` - Creation failed; nothing was created
+    #[instrument(name = "WorkflowService::create_workflow", skip_all, fields(tenant_id = %caller.tenant_id, workflow_name = %workflow.name, steps = workflow.steps.len()))]
+    pub async fn create_workflow(&self, caller: &Caller, workflow: CreateWorkflow) -> Result<WorkflowResponse, ServiceError> {
+        info!("Service: Creating workflow {} with {} steps", workflow.name, workflow.steps.len());
+
+        let order = workflow
+            .topological_order()
+            .map_err(|step| ServiceError::ValidationError(format!("Edges form a cycle through step {}", step)))?;
+
+        let context = enqueue_context(caller, self.idempotency_retention_hours);
+        let (workflow_row, inserted) =
+            WorkflowRepository::create(&self.pool, Uuid::new_v4(), &workflow, &order, &context).await?;
+
+        let steps: Vec<CreatedStep> = inserted
+            .into_iter()
+            .map(|(index, job_id)| {
+                let step = &workflow.steps[index];
+                metrics().jobs_created.with_label_values(&[step.name.as_str()]).inc();
+                CreatedStep { step: step.id.clone(), job_id }
+            })
+            .collect();
+
+        info!("Service: Workflow {} created with {} steps", workflow_row.id, steps.len());
+
+        Ok(WorkflowResponse {
+            message: format!("Workflow created with {} steps", steps.len()),
+            workflow: workflow_row,
+            steps,
+        })
+    }
+
+    /// Get a workflow and the state of each of its steps
+    #[instrument(name = "WorkflowService::get_workflow", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn get_workflow(&self, caller: &Caller, workflow_id: Uuid) -> Result<WorkflowStatusResponse, ServiceError> {
+        let (workflow, steps) = WorkflowRepository::find_with_steps(&self.pool, &caller.tenant_id, workflow_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or(ServiceError::WorkflowNotFound(workflow_id))?;
+
+        Ok(WorkflowStatusResponse { workflow, steps })
+    }
+
+    /// Cancel every step of a workflow that has not started yet
+    ///
+    /// # Returns
+    /// - `Ok(CancelWorkflowResponse)` - Workflow cancelled
+    /// - `Err(ServiceError::WorkflowNotFound)` - No such workflow for this tenant
+    /// - `Err(ServiceError::WorkflowFinished)` - The workflow already finished
+    #[instrument(name = "WorkflowService::cancel_workflow", skip(self, caller), fields(tenant_id = %caller.tenant_id))]
+    pub async fn cancel_workflow(&self, caller: &Caller, workflow_id: Uuid) -> Result<CancelWorkflowResponse, ServiceError> {
+        let cancelled = WorkflowRepository::cancel(&self.pool, &caller.tenant_id, workflow_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let Some((workflow, cancelled)) = cancelled else {
+            // Distinguish an unknown workflow from one that already finished
+            self.get_workflow(caller, workflow_id).await?;
+            return Err(ServiceError::WorkflowFinished(workflow_id));
+        };
+
+        info!("Service: Workflow {} cancelled ({} steps)", workflow_id, cancelled);
+
+        Ok(CancelWorkflowResponse {
+            message: format!("Workflow cancelled; {} pending steps cancelled", cancelled),
+            cancelled,
+            workflow,
+        })
+    }
+}
@@ -1,5 +1,19 @@
+use ipnet::IpNet;
 use std::env;
 
+/// Loopback, private, link-local (incl. cloud metadata), CGNAT and unspecified ranges
+pub const DEFAULT_WEBHOOK_DENY_CIDRS: &str = "127.0.0.0/8,10.0.0.0/8,172.16.0.0/12,192.168.0.0/16,169.254.0.0/16,\
+100.64.0.0/10,0.0.0.0/8,::1/128,::/128,fc00::/7,fe80::/10";
+
+/// Output format for all log layers (console and files)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text (default)
+    Pretty,
+    /// One JSON object per line, for log pipelines
+    Json,
+}
+
 /// Application configuration loaded from environment variables
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -26,6 +40,46 @@ pub struct Config {
     /// Directory for log files (daily rotation, separated by level)
     /// Default: "logs"
     pub log_dir: String,
+
+    /// Log output format: "pretty" or "json"
+    /// Default: pretty
+    pub log_format: LogFormat,
+
+    /// Hours an idempotency key keeps mapping to the job it created
+    /// Default: 24
+    pub idempotency_retention_hours: i32,
+
+    /// OTLP/HTTP collector base URL for trace export (e.g. http://localhost:4318)
+    /// Default: unset (tracing export disabled)
+    pub otel_endpoint: Option<String>,
+
+    /// Delivery attempts per completion webhook before it is marked failed
+    /// Default: 8
+    pub webhook_max_attempts: i32,
+
+    /// Delay before the first webhook retry; doubled per retry, capped at an hour
+    /// Default: 10
+    pub webhook_backoff_secs: i32,
+
+    /// Timeout of a single webhook request
+    /// Default: 10
+    pub webhook_timeout_secs: u64,
+
+    /// Address ranges webhooks may not be delivered to
+    /// Default: DEFAULT_WEBHOOK_DENY_CIDRS
+    pub webhook_deny_cidrs: Vec<IpNet>,
+
+    /// Interval between a worker's heartbeats in the `workers` table
+    /// Default: 10
+    pub worker_heartbeat_secs: u64,
+
+    /// Age of the last heartbeat after which a worker entry is removed
+    /// Default: 60
+    pub worker_stale_secs: u64,
+
+    /// Job names the workers of this process acquire; empty means every name
+    /// Default: empty
+    pub worker_queues: Vec<String>,
 }
 
 impl Config {
@@ -40,6 +94,16 @@ impl Config {
     /// - MAX_CONCURRENT_JOBS: Maximum concurrent jobs processing (semaphore permits) (default: 5)
     /// - NUM_WORKERS: Number of worker loops acquiring jobs (default: 3)
     /// - LOG_DIR: Directory for log files with daily rotation (default: "logs")
+    /// - LOG_FORMAT: Log output format, "pretty" or "json" (default: "pretty")
+    /// - IDEMPOTENCY_RETENTION_HOURS: Hours an idempotency key is remembered (default: 24)
+    /// - OTEL_EXPORTER_OTLP_ENDPOINT: OTLP/HTTP collector URL; enables trace export when set
+    /// - WEBHOOK_MAX_ATTEMPTS: Delivery attempts per completion webhook (default: 8)
+    /// - WEBHOOK_BACKOFF_SECS: First webhook retry delay, doubled per retry (default: 10)
+    /// - WEBHOOK_TIMEOUT_SECS: Timeout of a webhook request (default: 10)
+    /// - WEBHOOK_DENY_CIDRS: Comma-separated ranges webhooks may not reach (default: private and loopback ranges)
+    /// - WORKER_HEARTBEAT_SECS: Interval between worker heartbeats (default: 10)
+    /// - WORKER_STALE_SECS: Heartbeat age after which a worker is removed (default: 60)
+    /// - WORKER_QUEUES: Comma-separated job names this process's workers acquire (default: every name)
     ///
     /// Note: Ensure MAX_DB_CONNECTIONS >= NUM_WORKERS + MAX_CONCURRENT_JOBS + API_BUFFER
     pub fn from_env() -> Result<Self, String> {
@@ -77,6 +141,69 @@ impl Config {
         let log_dir = env::var("LOG_DIR")
             .unwrap_or_else(|_| "logs".to_string()); // Default: logs directory
 
+        // Parse LOG_FORMAT with default fallback; unknown values are rejected
+        let log_format = match env::var("LOG_FORMAT") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "json" => LogFormat::Json,
+                "pretty" => LogFormat::Pretty,
+                other => return Err(format!("LOG_FORMAT must be 'json' or 'pretty', got '{}'", other)),
+            },
+            Err(_) => LogFormat::Pretty, // Default: human-readable
+        };
+
+        // Parse IDEMPOTENCY_RETENTION_HOURS with default fallback
+        let idempotency_retention_hours = env::var("IDEMPOTENCY_RETENTION_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(24); // Default: 24 hours
+
+        // Parse OTEL_EXPORTER_OTLP_ENDPOINT; tracing export stays off when unset
+        let otel_endpoint = env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        // Parse webhook delivery settings with default fallbacks
+        let webhook_max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(8); // Default: 8 attempts
+
+        let webhook_backoff_secs = env::var("WEBHOOK_BACKOFF_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10); // Default: 10 seconds
+
+        let webhook_timeout_secs = env::var("WEBHOOK_TIMEOUT_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10); // Default: 10 seconds
+
+        // An empty WEBHOOK_DENY_CIDRS allows every destination; invalid ranges are rejected
+        let webhook_deny_cidrs = parse_cidrs(
+            "WEBHOOK_DENY_CIDRS",
+            &env::var("WEBHOOK_DENY_CIDRS").unwrap_or_else(|_| DEFAULT_WEBHOOK_DENY_CIDRS.to_string()),
+        )?;
+
+        // Parse worker registry settings with default fallbacks
+        let worker_heartbeat_secs = env::var("WORKER_HEARTBEAT_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(10); // Default: 10 seconds
+
+        let worker_stale_secs = env::var("WORKER_STALE_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60); // Default: 60 seconds
+
+        // Parse WORKER_QUEUES; unset or empty serves every job name
+        let worker_queues = env::var("WORKER_QUEUES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect();
+
         Ok(Config {
             database_url,
             max_payload_size,
@@ -84,6 +211,26 @@ impl Config {
             max_concurrent_jobs,
             num_workers,
             log_dir,
+            log_format,
+            idempotency_retention_hours,
+            otel_endpoint,
+            webhook_max_attempts,
+            webhook_backoff_secs,
+            webhook_timeout_secs,
+            webhook_deny_cidrs,
+            worker_heartbeat_secs,
+            worker_stale_secs,
+            worker_queues,
         })
     }
 }
+
+/// Parse a comma-separated list of CIDR ranges such as `10.0.0.0/8,fc00::/7`
+pub fn parse_cidrs(name: &str, value: &str) -> Result<Vec<IpNet>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|cidr| !cidr.is_empty())
+        .map(|cidr| cidr.parse().map_err(|_| format!("{} contains an invalid CIDR range '{}'", name, cidr)))
+        .collect()
+}
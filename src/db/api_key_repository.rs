@@ -0,0 +1,104 @@
+use sqlx::{Pool, Postgres};
+use tracing::{debug, instrument};
+use crate::db::models::ApiKeyRow;
+
+/// Repository for API key database operations
+pub struct ApiKeyRepository;
+
+impl ApiKeyRepository {
+    /// Store a new API key by its hash, with the secret that signs its webhooks, and return the stored record
+    pub async fn create(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        name: &str,
+        key_hash: &str,
+        key_prefix: &str,
+        scopes: &[String],
+        webhook_secret: &str,
+    ) -> Result<ApiKeyRow, sqlx::Error> {
+        debug!("Creating API key: tenant={}, name={}, scopes={:?}", tenant_id, name, scopes);
+
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+            INSERT INTO api_keys (tenant_id, name, key_hash, key_prefix, scopes, webhook_secret)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, tenant_id, name, key_prefix, scopes, created_at, revoked_at
+            "#,
+            tenant_id,
+            name,
+            key_hash,
+            key_prefix,
+            scopes,
+            webhook_secret
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Find a non-revoked API key by the SHA-256 hash of the presented key
+    #[instrument(name = "ApiKeyRepository::find_active_by_hash", skip_all)]
+    pub async fn find_active_by_hash(
+        pool: &Pool<Postgres>,
+        key_hash: &str,
+    ) -> Result<Option<ApiKeyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+            SELECT id, tenant_id, name, key_prefix, scopes, created_at, revoked_at
+            FROM api_keys
+            WHERE key_hash = $1 AND revoked_at IS NULL
+            "#,
+            key_hash
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// List all API keys, including revoked ones
+    pub async fn list(pool: &Pool<Postgres>) -> Result<Vec<ApiKeyRow>, sqlx::Error> {
+        sqlx::query_as!(
+            ApiKeyRow,
+            r#"
+            SELECT id, tenant_id, name, key_prefix, scopes, created_at, revoked_at
+            FROM api_keys
+            ORDER BY id ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Revoke an API key
+    ///
+    /// # Returns
+    /// - `Ok(true)` - Key was active and is now revoked
+    /// - `Ok(false)` - No active key with this id
+    pub async fn revoke(pool: &Pool<Postgres>, id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE api_keys SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL",
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replace the webhook signing secret of an active key
+    ///
+    /// # Returns
+    /// - `Ok(true)` - Secret replaced; every delivery sent from now on is signed with it
+    /// - `Ok(false)` - No active key with this id
+    pub async fn set_webhook_secret(pool: &Pool<Postgres>, id: i32, webhook_secret: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query!(
+            "UPDATE api_keys SET webhook_secret = $2 WHERE id = $1 AND revoked_at IS NULL",
+            id,
+            webhook_secret
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
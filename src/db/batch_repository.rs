@@ -0,0 +1,267 @@
+use serde_json::Value;
+use sqlx::{PgConnection, Pool, Postgres};
+use tracing::{debug, info, instrument, warn};
+use uuid::Uuid;
+use crate::api::job::Job;
+use crate::db::job_repository::{InsertError, JobRepository};
+use crate::db::models::{BatchCounts, BatchRow, EnqueueContext};
+
+/// Repository for batch database operations
+pub struct BatchRepository;
+
+impl BatchRepository {
+    /// Create a batch and insert its member jobs in one transaction
+    ///
+    /// Members are inserted like a bulk upload (quota, idempotency and unique keys
+    /// apply) with `batch_id` set to the new batch. A batch that ends up with no
+    /// pending members, e.g. because every job was a duplicate, finishes right away.
+    ///
+    /// # Returns
+    /// The batch as stored after the insert and `(index into jobs, id)` for every created member
+    #[instrument(name = "BatchRepository::create", skip_all, fields(tenant_id = %context.tenant_id, %batch_id, count = jobs.len()))]
+    pub async fn create(
+        pool: &Pool<Postgres>,
+        batch_id: Uuid,
+        name: &str,
+        on_complete: Option<&Value>,
+        on_failure: Option<&Value>,
+        jobs: &[Job],
+        context: &EnqueueContext,
+    ) -> Result<(BatchRow, Vec<(usize, i32)>), InsertError> {
+        debug!("Creating batch {} ({}) with {} jobs", batch_id, name, jobs.len());
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO batches (id, tenant_id, name, on_complete, on_failure)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            batch_id,
+            context.tenant_id,
+            name,
+            on_complete,
+            on_failure
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let context = EnqueueContext {
+            batch_id: Some(batch_id),
+            ..context.clone()
+        };
+        let ids = JobRepository::bulk_create_in(&mut tx, jobs, &context).await?;
+
+        // Members cancelled at insert (a parent already failed) count as finished
+        sqlx::query!(
+            r#"
+            UPDATE batches b
+            SET total = c.total, pending = c.pending, failed = c.failed
+            FROM (
+                SELECT
+                    COUNT(*)::INT AS total,
+                    (COUNT(*) FILTER (WHERE status <> 'cancelled'))::INT AS pending,
+                    (COUNT(*) FILTER (WHERE status = 'cancelled'))::INT AS failed
+                FROM jobs
+                WHERE tenant_id = $2 AND batch_id = $1
+            ) c
+            WHERE b.id = $1
+            "#,
+            batch_id,
+            context.tenant_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        Self::on_member_finished(&mut tx, batch_id, 0, 0).await?;
+
+        let batch = Self::find_in(&mut tx, &context.tenant_id, batch_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+
+        tx.commit().await?;
+
+        debug!("Batch {} created with {} jobs", batch_id, ids.len());
+        Ok((batch, ids))
+    }
+
+    /// Find a tenant's batch together with its member counts
+    #[instrument(name = "BatchRepository::find_with_counts", skip(pool))]
+    pub async fn find_with_counts(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        batch_id: Uuid,
+    ) -> Result<Option<(BatchRow, BatchCounts)>, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+
+        let Some(batch) = Self::find_in(&mut conn, tenant_id, batch_id).await? else {
+            return Ok(None);
+        };
+        let counts = Self::counts(&mut conn, batch_id).await?;
+
+        Ok(Some((batch, counts)))
+    }
+
+    /// Record members reaching a terminal status and finish the batch if none are pending any more
+    ///
+    /// Called in the transaction that moves `succeeded + failed` members to a terminal
+    /// status. The counters are updated under the batch row lock, so when the last two
+    /// members finish concurrently the second transaction to get the lock sees the
+    /// first one's counts and exactly one of them finishes the batch.
+    ///
+    /// A batch with any failed or cancelled member becomes `failed` and enqueues its `on_failure`
+    /// job; otherwise it becomes `succeeded` and enqueues `on_complete`.
+    pub async fn on_member_finished(
+        conn: &mut PgConnection,
+        batch_id: Uuid,
+        succeeded: i32,
+        failed: i32,
+    ) -> Result<(), sqlx::Error> {
+        let batch = sqlx::query_as!(
+            BatchRow,
+            r#"
+            SELECT id, tenant_id, name, status, on_complete, on_failure, follow_up_job_id, created_at, finished_at
+            FROM batches
+            WHERE id = $1 AND finished_at IS NULL
+            FOR UPDATE
+            "#,
+            batch_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        // Not a named batch (plain bulk upload) or already finished
+        let Some(batch) = batch else {
+            return Ok(());
+        };
+
+        let counts = sqlx::query_as!(
+            BatchCounts,
+            r#"
+            UPDATE batches
+            SET pending = pending - $2 - $3, succeeded = succeeded + $2, failed = failed + $3
+            WHERE id = $1
+            RETURNING total::BIGINT AS "total!", pending::BIGINT AS "pending!",
+                      succeeded::BIGINT AS "succeeded!", failed::BIGINT AS "failed!"
+            "#,
+            batch_id,
+            succeeded,
+            failed
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+        if counts.pending > 0 {
+            return Ok(());
+        }
+
+        let (status, follow_up) = if counts.failed > 0 {
+            ("failed", batch.on_failure.as_ref())
+        } else {
+            ("succeeded", batch.on_complete.as_ref())
+        };
+
+        let follow_up_job_id = match follow_up {
+            Some(spec) => Self::enqueue_follow_up(&mut *conn, &batch, spec).await?,
+            None => None,
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE batches
+            SET status = $2, finished_at = NOW(), follow_up_job_id = $3
+            WHERE id = $1
+            "#,
+            batch_id,
+            status,
+            follow_up_job_id
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        info!(
+            batch_id = %batch_id,
+            status,
+            follow_up_job_id,
+            "Batch finished: {} succeeded, {} failed",
+            counts.succeeded,
+            counts.failed
+        );
+
+        Ok(())
+    }
+
+    /// Insert the follow-up job described by `spec` (`{"name": ..., "payload": ...}`)
+    ///
+    /// An object payload gets the batch id added under `batch_id`. The follow-up is
+    /// not a member of the batch and does not count against the queued-job quota.
+    async fn enqueue_follow_up(
+        conn: &mut PgConnection,
+        batch: &BatchRow,
+        spec: &Value,
+    ) -> Result<Option<i32>, sqlx::Error> {
+        let Some(name) = spec.get("name").and_then(Value::as_str) else {
+            warn!(batch_id = %batch.id, "Follow-up job spec has no name; skipping");
+            return Ok(None);
+        };
+
+        let mut payload = spec.get("payload").cloned().unwrap_or_else(|| serde_json::json!({}));
+        if let Some(object) = payload.as_object_mut() {
+            object.insert("batch_id".to_string(), Value::String(batch.id.to_string()));
+        }
+
+        let job_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO jobs (tenant_id, name, status, payload)
+            VALUES ($1, $2, 'new', $3)
+            RETURNING id
+            "#,
+            batch.tenant_id,
+            name,
+            payload
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        JobRepository::record_event(&mut *conn, job_id, None, "new", None, 0, None).await?;
+
+        debug!("Enqueued follow-up job {} for batch {}", job_id, batch.id);
+        Ok(Some(job_id))
+    }
+
+    async fn find_in(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        batch_id: Uuid,
+    ) -> Result<Option<BatchRow>, sqlx::Error> {
+        sqlx::query_as!(
+            BatchRow,
+            r#"
+            SELECT id, tenant_id, name, status, on_complete, on_failure, follow_up_job_id, created_at, finished_at
+            FROM batches
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+            batch_id,
+            tenant_id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn counts(
+        conn: &mut PgConnection,
+        batch_id: Uuid,
+    ) -> Result<BatchCounts, sqlx::Error> {
+        sqlx::query_as!(
+            BatchCounts,
+            r#"
+            SELECT total::BIGINT AS "total!", pending::BIGINT AS "pending!",
+                   succeeded::BIGINT AS "succeeded!", failed::BIGINT AS "failed!"
+            FROM batches
+            WHERE id = $1
+            "#,
+            batch_id
+        )
+        .fetch_one(conn)
+        .await
+    }
+}
@@ -0,0 +1,394 @@
+use clap::{Parser, Subcommand};
+use sqlx::{Pool, Postgres};
+use std::time::Instant;
+use tracing::info;
+
+use crate::api::auth::{self, Scope};
+use crate::api::job::models::{JobStatus, ParentFailure, UniqueScope};
+use crate::api::job::Job;
+use crate::db::api_key_repository::ApiKeyRepository;
+use crate::db::job_repository::JobRepository;
+use crate::db::migrations;
+use crate::db::models::EnqueueContext;
+use crate::db::tenant_repository::TenantRepository;
+use crate::worker::webhook_dispatcher;
+
+/// Command line interface for the job processor
+///
+/// Without a subcommand the server starts as usual.
+#[derive(Parser)]
+#[command(name = "job-processor", about = "Bounded job processing server")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run database migrations and exit
+    Migrate,
+
+    /// Rollback the last N migrations
+    Rollback {
+        /// Number of migrations to rollback
+        #[arg(long, default_value_t = 1)]
+        steps: i64,
+    },
+
+    /// Rollback all migrations and re-apply them
+    Refresh,
+
+    /// Manage API keys
+    #[command(subcommand)]
+    ApiKey(ApiKeyCommand),
+
+    /// Manage tenants and their quotas
+    #[command(subcommand)]
+    Tenant(TenantCommand),
+
+    /// Measure bulk insert throughput
+    ///
+    /// Inserts `count` jobs with status 'success' (so running workers ignore them)
+    /// into a dedicated tenant, then deletes exactly those jobs again.
+    BenchBulkInsert {
+        /// Number of jobs to insert
+        #[arg(long, default_value_t = 100_000)]
+        count: usize,
+
+        /// Tenant to insert into; created without quotas if missing
+        #[arg(long, default_value = "bench")]
+        tenant: String,
+
+        /// Keep the inserted jobs instead of deleting them
+        #[arg(long)]
+        keep: bool,
+    },
+
+    /// Run a local HTTP stand-in that prints every webhook it receives
+    ///
+    /// Point a job's `callback_url` at `http://127.0.0.1:<port>/` to try out
+    /// completion webhooks without an external receiver.
+    WebhookEcho {
+        #[arg(long, default_value_t = 9000)]
+        port: u16,
+
+        /// Verify `X-Webhook-Signature` with this secret
+        #[arg(long)]
+        secret: Option<String>,
+
+        /// Answer with this status code, e.g. 500 to exercise retries
+        #[arg(long, default_value_t = 200)]
+        status: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApiKeyCommand {
+    /// Create a new API key; the key is printed once and only its hash is stored
+    Create {
+        /// Human-readable name of the key owner
+        #[arg(long)]
+        name: String,
+
+        /// Tenant the key belongs to; jobs created with it are owned by this tenant
+        #[arg(long, default_value = "default")]
+        tenant: String,
+
+        /// Comma-separated scopes: jobs:read, jobs:write, metrics:read, admin
+        #[arg(long, value_delimiter = ',', required = true)]
+        scopes: Vec<String>,
+    },
+
+    /// List all API keys
+    List,
+
+    /// Revoke an API key by id
+    Revoke {
+        #[arg(long)]
+        id: i32,
+    },
+
+    /// Generate and print a new webhook signing secret for a key
+    RotateWebhookSecret {
+        #[arg(long)]
+        id: i32,
+    },
+}
+
+#[derive(Subcommand)]
+enum TenantCommand {
+    /// Create a tenant or replace its quotas (omitted quotas mean unlimited)
+    Set {
+        #[arg(long)]
+        id: String,
+
+        /// Maximum jobs processing at once for this tenant
+        #[arg(long)]
+        max_concurrent: Option<i32>,
+
+        /// Maximum jobs waiting in 'new' for this tenant
+        #[arg(long)]
+        max_queued: Option<i32>,
+    },
+
+    /// List all tenants and their quotas
+    List,
+}
+
+/// Run the command given on the command line, if any
+///
+/// Returns `Ok(())` without doing anything when no subcommand was given so the
+/// caller can start the server. When a subcommand runs, the process exits after it.
+pub async fn run(pool: Pool<Postgres>) -> Result<(), String> {
+    let Some(command) = Cli::parse().command else {
+        return Ok(());
+    };
+
+    match command {
+        Command::Migrate => {
+            // Migrations already ran on startup; nothing else to do
+            info!("Migrations are up to date");
+        }
+        Command::Rollback { steps } => {
+            migrations::rollback_migrations(&pool, steps)
+                .await
+                .map_err(|e| format!("Rollback failed: {}", e))?;
+        }
+        Command::Refresh => {
+            migrations::refresh_database(&pool)
+                .await
+                .map_err(|e| format!("Refresh failed: {}", e))?;
+        }
+        Command::ApiKey(command) => run_api_key_command(&pool, command).await?,
+        Command::Tenant(command) => run_tenant_command(&pool, command).await?,
+        Command::BenchBulkInsert { count, tenant, keep } => {
+            run_bench_bulk_insert(&pool, count, &tenant, keep).await?
+        }
+        Command::WebhookEcho { port, secret, status } => run_webhook_echo(port, secret, status).await?,
+    }
+
+    pool.close().await;
+    std::process::exit(0);
+}
+
+async fn run_api_key_command(pool: &Pool<Postgres>, command: ApiKeyCommand) -> Result<(), String> {
+    match command {
+        ApiKeyCommand::Create { name, tenant, scopes } => {
+            for scope in &scopes {
+                if Scope::parse(scope).is_none() {
+                    return Err(format!(
+                        "Unknown scope '{}'. Valid scopes: jobs:read, jobs:write, metrics:read, admin",
+                        scope
+                    ));
+                }
+            }
+
+            let (key, prefix) = auth::generate_key();
+            let webhook_secret = auth::generate_webhook_secret();
+            let row = ApiKeyRepository::create(pool, &tenant, &name, &auth::hash_key(&key), &prefix, &scopes, &webhook_secret)
+                .await
+                .map_err(|e| format!("Failed to create API key (does tenant '{}' exist?): {}", tenant, e))?;
+
+            println!(
+                "Created API key {} ({}) for tenant {} with scopes {}",
+                row.id,
+                row.name,
+                row.tenant_id,
+                row.scopes.join(",")
+            );
+            println!("{}", key);
+            println!("Store this key now; it cannot be shown again.");
+            println!("Webhook signing secret: {}", webhook_secret);
+        }
+        ApiKeyCommand::List => {
+            let keys = ApiKeyRepository::list(pool)
+                .await
+                .map_err(|e| format!("Failed to list API keys: {}", e))?;
+
+            for key in keys {
+                let state = if key.revoked_at.is_some() { "revoked" } else { "active" };
+                println!(
+                    "{}\t{}\t{}\t{}...\t{}\t{}",
+                    key.id,
+                    key.tenant_id,
+                    key.name,
+                    key.key_prefix,
+                    key.scopes.join(","),
+                    state
+                );
+            }
+        }
+        ApiKeyCommand::Revoke { id } => {
+            let revoked = ApiKeyRepository::revoke(pool, id)
+                .await
+                .map_err(|e| format!("Failed to revoke API key: {}", e))?;
+
+            if !revoked {
+                return Err(format!("No active API key with id {}", id));
+            }
+            println!("Revoked API key {}", id);
+        }
+        ApiKeyCommand::RotateWebhookSecret { id } => {
+            let webhook_secret = auth::generate_webhook_secret();
+            let updated = ApiKeyRepository::set_webhook_secret(pool, id, &webhook_secret)
+                .await
+                .map_err(|e| format!("Failed to rotate webhook secret: {}", e))?;
+
+            if !updated {
+                return Err(format!("No active API key with id {}", id));
+            }
+            println!("Webhook signing secret for API key {}: {}", id, webhook_secret);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_tenant_command(pool: &Pool<Postgres>, command: TenantCommand) -> Result<(), String> {
+    match command {
+        TenantCommand::Set { id, max_concurrent, max_queued } => {
+            let tenant = TenantRepository::upsert(pool, &id, max_concurrent, max_queued)
+                .await
+                .map_err(|e| format!("Failed to save tenant: {}", e))?;
+
+            println!(
+                "Tenant {}: max_concurrent_jobs={}, max_queued_jobs={}",
+                tenant.id,
+                format_quota(tenant.max_concurrent_jobs),
+                format_quota(tenant.max_queued_jobs)
+            );
+        }
+        TenantCommand::List => {
+            let tenants = TenantRepository::list(pool)
+                .await
+                .map_err(|e| format!("Failed to list tenants: {}", e))?;
+
+            for tenant in tenants {
+                println!(
+                    "{}\t{}\t{}",
+                    tenant.id,
+                    format_quota(tenant.max_concurrent_jobs),
+                    format_quota(tenant.max_queued_jobs)
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_bench_bulk_insert(
+    pool: &Pool<Postgres>,
+    count: usize,
+    tenant: &str,
+    keep: bool,
+) -> Result<(), String> {
+    let existing = TenantRepository::list(pool)
+        .await
+        .map_err(|e| format!("Failed to list tenants: {}", e))?;
+    if !existing.iter().any(|t| t.id == tenant) {
+        TenantRepository::upsert(pool, tenant, None, None)
+            .await
+            .map_err(|e| format!("Failed to create tenant: {}", e))?;
+    }
+
+    let jobs: Vec<Job> = (0..count)
+        .map(|i| Job {
+            name: "bench".to_string(),
+            status: JobStatus::Success,
+            idempotency_key: None,
+            unique_key: None,
+            unique_scope: UniqueScope::default(),
+            unique_window_secs: None,
+            payload: Some(serde_json::json!({ "n": i })),
+            depends_on: Vec::new(),
+            on_parent_failure: ParentFailure::default(),
+            callback_url: None,
+        })
+        .collect();
+
+    let context = EnqueueContext {
+        tenant_id: tenant.to_string(),
+        trace_context: None,
+        request_id: None,
+        idempotency_retention_hours: 24,
+        batch_id: None,
+        api_key_id: None,
+    };
+
+    let started = Instant::now();
+    let ids = JobRepository::bulk_create(pool, &jobs, &context)
+        .await
+        .map_err(|e| format!("Bulk insert failed: {}", e))?
+        .created;
+    let elapsed = started.elapsed();
+
+    println!(
+        "Inserted {} jobs in {:.2?} ({:.0} jobs/s)",
+        ids.len(),
+        elapsed,
+        ids.len() as f64 / elapsed.as_secs_f64()
+    );
+
+    if !keep {
+        // Only the jobs this run inserted; the tenant may hold real ones
+        let ids: Vec<i32> = ids.iter().map(|(_, id)| *id).collect();
+        let deleted = JobRepository::delete_by_ids(pool, &ids)
+            .await
+            .map_err(|e| format!("Failed to clean up benchmark jobs: {}", e))?;
+        println!("Deleted {} benchmark jobs", deleted);
+    }
+
+    Ok(())
+}
+
+fn format_quota(quota: Option<i32>) -> String {
+    quota.map_or_else(|| "unlimited".to_string(), |q| q.to_string())
+}
+
+async fn run_webhook_echo(port: u16, secret: Option<String>, status: u16) -> Result<(), String> {
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use actix_web::http::StatusCode;
+
+    let status = StatusCode::from_u16(status).map_err(|e| format!("Invalid status: {}", e))?;
+    println!("Listening for webhooks on http://127.0.0.1:{}/ (answering {})", port, status);
+
+    HttpServer::new(move || {
+        let secret = secret.clone();
+        App::new().default_service(web::to(move |req: HttpRequest, body: web::Bytes| {
+            let secret = secret.clone();
+            async move {
+                let header = |name: &str| {
+                    req.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string()
+                };
+                let body = String::from_utf8_lossy(&body);
+
+                let signature = match &secret {
+                    Some(secret) => {
+                        let expected = format!(
+                            "sha256={}",
+                            webhook_dispatcher::sign(secret, &header(webhook_dispatcher::TIMESTAMP_HEADER), &body)
+                        );
+                        if header(webhook_dispatcher::SIGNATURE_HEADER) == expected { "valid" } else { "INVALID" }
+                    }
+                    None => "not checked",
+                };
+
+                println!(
+                    "{} {} delivery={} signature={}\n{}",
+                    req.method(),
+                    req.path(),
+                    header(webhook_dispatcher::DELIVERY_ID_HEADER),
+                    signature,
+                    body
+                );
+                HttpResponse::build(status).finish()
+            }
+        }))
+    })
+    .bind(("127.0.0.1", port))
+    .map_err(|e| format!("Failed to bind port {}: {}", port, e))?
+    .run()
+    .await
+    .map_err(|e| format!("Webhook echo server failed: {}", e))
+}
@@ -1,78 +1,720 @@
-use sqlx::{Pool, Postgres, Row};
-use tracing::{debug, info};
+use chrono::NaiveDateTime;
+use sqlx::{PgConnection, Pool, Postgres};
+use std::fmt;
+use tracing::{debug, info, instrument, warn};
+use crate::api::job::models::ParentFailure;
 use crate::api::job::Job;
-use crate::db::models::JobRow;
+use crate::db::batch_repository::BatchRepository;
+use crate::db::webhook_repository::WebhookRepository;
+use crate::db::workflow_repository::WorkflowRepository;
+use crate::db::models::{EnqueueContext, JobEventMessage, JobEventRow, JobRow};
+use crate::worker::handler::{SpawnedJob, SpawnedJobs};
+use uuid::Uuid;
+
+/// Errors from job inserts that enforce the tenant's queued-job quota
+#[derive(Debug)]
+pub enum InsertError {
+    /// Database operation failed
+    Database(sqlx::Error),
+
+    /// Inserting would exceed the tenant's `max_queued_jobs`
+    QuotaExceeded { limit: i64, queued: i64 },
+
+    /// `depends_on` names jobs that do not exist for the tenant
+    UnknownParents(Vec<i32>),
+
+    /// `depends_on` would make the job its own ancestor
+    DependencyCycle(i32),
+
+    /// Jobs were skipped by `ON CONFLICT` although neither their idempotency key
+    /// nor their unique key is taken, i.e. some other unique index conflicted
+    UnexpectedConflict(usize),
+}
+
+impl From<sqlx::Error> for InsertError {
+    fn from(e: sqlx::Error) -> Self {
+        InsertError::Database(e)
+    }
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InsertError::Database(e) => write!(f, "Database error: {}", e),
+            InsertError::QuotaExceeded { limit, queued } => {
+                write!(f, "Queued job quota exceeded: {} of {} in use", queued, limit)
+            }
+            InsertError::UnknownParents(ids) => write!(f, "Unknown parent jobs: {:?}", ids),
+            InsertError::DependencyCycle(id) => write!(f, "Dependency cycle through job {}", id),
+            InsertError::UnexpectedConflict(count) => {
+                write!(f, "{} jobs conflicted with an existing row other than by key", count)
+            }
+        }
+    }
+}
+
+/// Outcome of `bulk_create`, as `(index into jobs, id)` pairs in input order
+#[derive(Debug, Default)]
+pub struct BulkInsert {
+    /// Jobs inserted by the upload
+    pub created: Vec<(usize, i32)>,
+    /// Jobs skipped as duplicates, with the id of the job holding their key
+    pub duplicates: Vec<(usize, i32)>,
+}
+
+/// Maximum jobs per `UNNEST` insert statement in `bulk_create`
+const BULK_INSERT_CHUNK_SIZE: usize = 5000;
 
 /// Repository for Job database operations
 pub struct JobRepository;
 
 impl JobRepository {
     /// Create a new job in the database and return the full job record
+    ///
+    /// The tenant's queued-job quota is checked and the initial `new` event is
+    /// written in the same transaction as the job.
+    ///
+    /// If the job carries an idempotency key that was used by the same tenant
+    /// within the retention window, nothing is inserted and the original job is
+    /// returned instead. Keys older than the window are released first so they
+    /// can be reused.
+    ///
+    /// Likewise, if another job of the tenant still holds the job's `unique_key`,
+    /// that job is returned and nothing is inserted.
+    ///
+    /// # Returns
+    /// - `Ok((job, true))` - Job was created
+    /// - `Ok((job, false))` - Existing job for the idempotency or unique key
+    #[instrument(name = "JobRepository::create", skip_all, fields(tenant_id = %context.tenant_id, job_name = %job.name))]
     pub async fn create(
         pool: &Pool<Postgres>,
         job: &Job,
-    ) -> Result<JobRow, sqlx::Error> {
+        context: &EnqueueContext,
+    ) -> Result<(JobRow, bool), InsertError> {
         debug!("Creating job: name={}, status={:?}", job.name, job.status);
 
         let status_str = format!("{:?}", job.status).to_lowercase();
 
+        let mut tx = pool.begin().await?;
+
+        if let Some(key) = &job.idempotency_key {
+            Self::release_expired_idempotency_keys(&mut tx, context, std::slice::from_ref(key)).await?;
+
+            if let Some(existing) = Self::find_by_idempotency_key(&mut tx, &context.tenant_id, key).await? {
+                debug!("Idempotency key already used by job {}", existing.id);
+                tx.rollback().await?;
+                return Ok((existing, false));
+            }
+        }
+
+        if let Some(key) = &job.unique_key {
+            Self::release_expired_unique_locks(&mut tx, &context.tenant_id, std::slice::from_ref(key)).await?;
+
+            if let Some(existing) = Self::find_by_unique_lock(&mut tx, &context.tenant_id, key).await? {
+                debug!("Unique key held by job {}", existing.id);
+                tx.rollback().await?;
+                return Ok((existing, false));
+            }
+        }
+
+        Self::check_queue_quota(&mut tx, &context.tenant_id, 1).await?;
+
+        // A concurrent request with the same idempotency or unique key may win the
+        // race; DO NOTHING then returns no row and we fall back to the job it created
         let row = sqlx::query_as!(
             JobRow,
             r#"
-            INSERT INTO jobs (name, status)
-            VALUES ($1, $2)
-            RETURNING id, name, status, created_at, updated_at
+            INSERT INTO jobs (
+                tenant_id, name, status, trace_context, request_id, idempotency_key,
+                unique_key, unique_scope, unique_lock, unique_until, payload, batch_id, on_parent_failure,
+                callback_url, api_key_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, NOW() + make_interval(secs => $10), $11, $12, $13, $14, $15)
+            ON CONFLICT DO NOTHING
+            RETURNING id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
             "#,
+            context.tenant_id,
             job.name,
-            status_str
+            status_str,
+            context.trace_context,
+            context.request_id,
+            job.idempotency_key,
+            job.unique_key,
+            job.unique_key.as_ref().map(|_| job.unique_scope.as_str()),
+            job.initial_unique_lock(),
+            job.unique_window_secs.map(f64::from),
+            job.payload,
+            context.batch_id,
+            job.on_parent_failure.as_str(),
+            job.callback_url,
+            context.api_key_id
         )
-        .fetch_one(pool)
+        .fetch_optional(&mut *tx)
         .await?;
 
+        let Some(row) = row else {
+            // Read committed: these statements see the row committed by the winner
+            let mut existing = None;
+            if let Some(key) = &job.idempotency_key {
+                existing = Self::find_by_idempotency_key(&mut tx, &context.tenant_id, key).await?;
+            }
+            if existing.is_none() {
+                if let Some(key) = &job.unique_key {
+                    existing = Self::find_by_unique_lock(&mut tx, &context.tenant_id, key).await?;
+                }
+            }
+            // Only a taken idempotency or unique key makes this a duplicate
+            let existing = existing.ok_or(InsertError::UnexpectedConflict(1))?;
+            tx.rollback().await?;
+            return Ok((existing, false));
+        };
+
+        let mut row = row;
+        Self::record_event(&mut tx, row.id, None, &row.status, None, row.attempts, None).await?;
+
+        if !job.depends_on.is_empty() {
+            if let Some(status) = Self::attach_dependencies(&mut tx, &context.tenant_id, row.id, &job.depends_on, job.on_parent_failure).await? {
+                row.status = status.to_string();
+            }
+        }
+
+        tx.commit().await?;
+
         debug!("Job created with id={}", row.id);
-        Ok(row)
+        Ok((row, true))
     }
 
     /// Bulk insert multiple jobs in a single transaction
-    /// Returns the number of rows inserted
+    /// Returns the inserted jobs and, for every skipped duplicate, the original job
+    ///
+    /// Rows are sent as one array per column and expanded with `UNNEST`, so each
+    /// statement binds a fixed number of parameters regardless of how many jobs it
+    /// carries. Jobs are inserted `BULK_INSERT_CHUNK_SIZE` at a time, all in the same
+    /// transaction.
+    ///
+    /// Either all jobs fit in the tenant's queued-job quota and are inserted, or none are.
+    /// Jobs whose idempotency key was already used within the retention window,
+    /// or whose unique key is still held (including earlier in the same file),
+    /// are skipped and reported with the job that used or holds the key.
+    #[instrument(name = "JobRepository::bulk_create", skip_all, fields(tenant_id = %context.tenant_id, count = jobs.len()))]
     pub async fn bulk_create(
         pool: &Pool<Postgres>,
         jobs: &[Job],
-    ) -> Result<u64, sqlx::Error> {
+        context: &EnqueueContext,
+    ) -> Result<BulkInsert, InsertError> {
         if jobs.is_empty() {
             debug!("Bulk create called with empty job list");
-            return Ok(0);
+            return Ok(BulkInsert::default());
         }
 
         debug!("Starting bulk insert of {} jobs", jobs.len());
 
-        // Build dynamic SQL for bulk insert
-        let mut query = String::from("INSERT INTO jobs (name, status) VALUES ");
-        let mut values = Vec::new();
+        let mut tx = pool.begin().await?;
+        let created = Self::bulk_create_in(&mut tx, jobs, context).await?;
+        let duplicates = Self::find_originals(&mut tx, jobs, &created, &context.tenant_id).await?;
+        tx.commit().await?;
+
+        debug!("Bulk insert completed: {} rows inserted, {} duplicates", created.len(), duplicates.len());
 
-        for (i, job) in jobs.iter().enumerate() {
-            let status_str = format!("{:?}", job.status).to_lowercase();
+        Ok(BulkInsert { created, duplicates })
+    }
 
-            if i > 0 {
-                query.push_str(", ");
+    /// Find the original job of every job `bulk_create_in` skipped
+    ///
+    /// `created` lists the inserted jobs in input order. Like `create`, an
+    /// idempotency key match wins over a unique key match.
+    async fn find_originals(
+        conn: &mut PgConnection,
+        jobs: &[Job],
+        created: &[(usize, i32)],
+        tenant_id: &str,
+    ) -> Result<Vec<(usize, i32)>, sqlx::Error> {
+        if created.len() == jobs.len() {
+            return Ok(Vec::new());
+        }
+
+        let mut indexes = Vec::new();
+        let mut keys = Vec::new();
+        let mut locks = Vec::new();
+        let mut next = created.iter().map(|(index, _)| *index).peekable();
+        for (index, job) in jobs.iter().enumerate() {
+            if next.peek() == Some(&index) {
+                next.next();
+                continue;
             }
-            query.push_str(&format!("(${}, ${})", i * 2 + 1, i * 2 + 2));
+            indexes.push(index as i64);
+            keys.push(job.idempotency_key.as_deref());
+            locks.push(job.initial_unique_lock());
+        }
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.index AS "index!", COALESCE(
+                (SELECT j.id FROM jobs j WHERE j.tenant_id = $4 AND j.idempotency_key = s.idempotency_key),
+                (SELECT j.id FROM jobs j WHERE j.tenant_id = $4 AND j.unique_lock = s.unique_lock)
+            ) AS id
+            FROM UNNEST($1::BIGINT[], $2::VARCHAR[], $3::VARCHAR[]) AS s(index, idempotency_key, unique_lock)
+            ORDER BY s.index
+            "#,
+            &indexes,
+            &keys as &[Option<&str>],
+            &locks as &[Option<&str>],
+            tenant_id
+        )
+        .fetch_all(conn)
+        .await?;
+
+        // insert_chunk already failed the upload for skipped rows without a taken key
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| row.id.map(|id| (row.index as usize, id)))
+            .collect())
+    }
+
+    /// Body of `bulk_create` on a caller-owned transaction
+    ///
+    /// Lets other repositories insert jobs atomically with their own rows.
+    pub async fn bulk_create_in(
+        conn: &mut PgConnection,
+        jobs: &[Job],
+        context: &EnqueueContext,
+    ) -> Result<Vec<(usize, i32)>, InsertError> {
+        let keys: Vec<String> = jobs.iter().filter_map(|job| job.idempotency_key.clone()).collect();
+        let unique_keys: Vec<String> = jobs.iter().filter_map(|job| job.unique_key.clone()).collect();
+
+        if !keys.is_empty() {
+            Self::release_expired_idempotency_keys(&mut *conn, context, &keys).await?;
+        }
+
+        if !unique_keys.is_empty() {
+            Self::release_expired_unique_locks(&mut *conn, &context.tenant_id, &unique_keys).await?;
+        }
+
+        Self::check_queue_quota(&mut *conn, &context.tenant_id, jobs.len() as i64).await?;
+
+        let mut ids = Vec::with_capacity(jobs.len());
+        for (chunk_index, chunk) in jobs.chunks(BULK_INSERT_CHUNK_SIZE).enumerate() {
+            let offset = chunk_index * BULK_INSERT_CHUNK_SIZE;
+            let inserted = Self::insert_chunk(&mut *conn, chunk, context).await?;
+            ids.extend(inserted.into_iter().map(|(ordinal, id)| (offset + ordinal as usize - 1, id)));
+        }
+
+        // Dependencies are rare in bulk uploads and are attached row by row
+        for &(index, id) in &ids {
+            if !jobs[index].depends_on.is_empty() {
+                Self::attach_dependencies(&mut *conn, &context.tenant_id, id, &jobs[index].depends_on, jobs[index].on_parent_failure).await?;
+            }
+        }
+
+        Ok(ids)
+    }
 
-            values.push(job.name.clone());
-            values.push(status_str);
+    /// Insert one chunk of a bulk upload with a single `UNNEST` statement
+    ///
+    /// The initial event of every inserted job is written by the same statement.
+    /// Ids are drawn from the sequence before inserting so each one can be reported
+    /// with the 1-based ordinal of its input row; rows skipped by `ON CONFLICT`
+    /// just leave a gap in the sequence.
+    ///
+    /// A skipped row is only a duplicate if its idempotency key or unique key is
+    /// now taken, by an earlier job or an earlier row of the upload. Any other
+    /// skipped row conflicted on a different unique index and fails the chunk.
+    async fn insert_chunk(
+        conn: &mut PgConnection,
+        jobs: &[Job],
+        context: &EnqueueContext,
+    ) -> Result<Vec<(i64, i32)>, InsertError> {
+        let mut names = Vec::with_capacity(jobs.len());
+        let mut statuses = Vec::with_capacity(jobs.len());
+        let mut idempotency_keys = Vec::with_capacity(jobs.len());
+        let mut unique_keys = Vec::with_capacity(jobs.len());
+        let mut unique_scopes = Vec::with_capacity(jobs.len());
+        let mut unique_locks = Vec::with_capacity(jobs.len());
+        let mut unique_window_secs = Vec::with_capacity(jobs.len());
+        let mut payloads = Vec::with_capacity(jobs.len());
+        let mut parent_failure_policies = Vec::with_capacity(jobs.len());
+        let mut callback_urls = Vec::with_capacity(jobs.len());
+
+        for job in jobs {
+            names.push(job.name.as_str());
+            statuses.push(format!("{:?}", job.status).to_lowercase());
+            idempotency_keys.push(job.idempotency_key.as_deref());
+            unique_keys.push(job.unique_key.as_deref());
+            unique_scopes.push(job.unique_key.as_ref().map(|_| job.unique_scope.as_str()));
+            unique_locks.push(job.initial_unique_lock());
+            unique_window_secs.push(job.unique_window_secs.map(f64::from));
+            payloads.push(job.payload.clone());
+            parent_failure_policies.push(job.on_parent_failure.as_str());
+            callback_urls.push(job.callback_url.as_deref());
         }
 
-        // Execute bulk insert
-        let mut query_builder = sqlx::query(&query);
-        for value in values {
-            query_builder = query_builder.bind(value);
+        // Tenant, trace context, request id, batch id and API key are shared by all rows
+        // The input CTE calls nextval, so Postgres evaluates it exactly once
+        let inserted: Vec<(i64, i32)> = sqlx::query_as(
+            r#"
+            WITH input AS (
+                SELECT nextval(pg_get_serial_sequence('jobs', 'id'))::INT AS id, j.*
+                FROM UNNEST(
+                    $1::VARCHAR[], $2::VARCHAR[], $3::VARCHAR[], $4::VARCHAR[],
+                    $5::VARCHAR[], $6::VARCHAR[], $7::FLOAT8[], $8::JSONB[], $9::VARCHAR[],
+                    $10::TEXT[]
+                ) WITH ORDINALITY AS j(
+                    name, status, idempotency_key, unique_key, unique_scope, unique_lock,
+                    unique_window_secs, payload, on_parent_failure, callback_url, ordinal
+                )
+            ), inserted AS (
+                INSERT INTO jobs (
+                    id, name, status, idempotency_key, unique_key, unique_scope, unique_lock,
+                    unique_until, payload, on_parent_failure, callback_url,
+                    tenant_id, trace_context, request_id, batch_id, api_key_id
+                )
+                SELECT id, name, status, idempotency_key, unique_key, unique_scope, unique_lock,
+                       NOW() + make_interval(secs => unique_window_secs), payload, on_parent_failure, callback_url,
+                       $11, $12, $13, $14, $15
+                FROM input
+                ORDER BY ordinal
+                ON CONFLICT DO NOTHING
+                RETURNING id, status
+            ), events AS (
+                INSERT INTO job_events (job_id, to_status)
+                SELECT id, status FROM inserted
+            )
+            SELECT input.ordinal, inserted.id
+            FROM inserted
+            JOIN input USING (id)
+            ORDER BY input.ordinal
+            "#,
+        )
+        .bind(&names)
+        .bind(&statuses)
+        .bind(&idempotency_keys)
+        .bind(&unique_keys)
+        .bind(&unique_scopes)
+        .bind(&unique_locks)
+        .bind(&unique_window_secs)
+        .bind(&payloads)
+        .bind(&parent_failure_policies)
+        .bind(&callback_urls)
+        .bind(&context.tenant_id)
+        .bind(&context.trace_context)
+        .bind(&context.request_id)
+        .bind(context.batch_id)
+        .bind(context.api_key_id)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if inserted.len() < jobs.len() {
+            let mut ordinals = Vec::new();
+            let mut skipped_keys = Vec::new();
+            let mut skipped_locks = Vec::new();
+            let mut next = inserted.iter().map(|(ordinal, _)| *ordinal).peekable();
+            for (index, job) in jobs.iter().enumerate() {
+                let ordinal = index as i64 + 1;
+                if next.peek() == Some(&ordinal) {
+                    next.next();
+                    continue;
+                }
+                ordinals.push(ordinal);
+                skipped_keys.push(job.idempotency_key.as_deref());
+                skipped_locks.push(job.initial_unique_lock());
+            }
+
+            let unexplained = sqlx::query_scalar!(
+                r#"
+                SELECT COUNT(*) AS "count!"
+                FROM UNNEST($1::BIGINT[], $2::VARCHAR[], $3::VARCHAR[]) AS s(ordinal, idempotency_key, unique_lock)
+                WHERE NOT EXISTS (
+                    SELECT 1 FROM jobs j
+                    WHERE j.tenant_id = $4
+                      AND (j.idempotency_key = s.idempotency_key OR j.unique_lock = s.unique_lock)
+                )
+                "#,
+                &ordinals,
+                &skipped_keys as &[Option<&str>],
+                &skipped_locks as &[Option<&str>],
+                context.tenant_id
+            )
+            .fetch_one(&mut *conn)
+            .await?;
+
+            if unexplained > 0 {
+                return Err(InsertError::UnexpectedConflict(unexplained as usize));
+            }
         }
 
-        let result = query_builder.execute(pool).await?;
-        let rows_affected = result.rows_affected();
-        debug!("Bulk insert completed: {} rows inserted", rows_affected);
+        Ok(inserted)
+    }
 
-        Ok(rows_affected)
+    /// Find a tenant's job by idempotency key
+    async fn find_by_idempotency_key(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        key: &str,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
+            FROM jobs
+            WHERE tenant_id = $1 AND idempotency_key = $2
+            "#,
+            tenant_id,
+            key
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Release idempotency keys whose retention window has passed so they can be reused
+    async fn release_expired_idempotency_keys(
+        conn: &mut PgConnection,
+        context: &EnqueueContext,
+        keys: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET idempotency_key = NULL
+            WHERE tenant_id = $1
+              AND idempotency_key = ANY($2)
+              AND created_at < NOW() - make_interval(hours => $3)
+            "#,
+            context.tenant_id,
+            keys,
+            context.idempotency_retention_hours
+        )
+        .execute(conn)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            debug!("Released {} expired idempotency key(s)", result.rows_affected());
+        }
+
+        Ok(())
+    }
+
+    /// Find the job of a tenant currently holding a unique key
+    async fn find_by_unique_lock(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        key: &str,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
+            FROM jobs
+            WHERE tenant_id = $1 AND unique_lock = $2
+            "#,
+            tenant_id,
+            key
+        )
+        .fetch_optional(conn)
+        .await
+    }
+
+    /// Release window-scoped unique keys whose window has passed
+    ///
+    /// Queued and queued-or-running holds are released by status transitions instead.
+    async fn release_expired_unique_locks(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        keys: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE jobs
+            SET unique_lock = NULL
+            WHERE tenant_id = $1
+              AND unique_lock = ANY($2)
+              AND unique_until <= NOW()
+            "#,
+            tenant_id,
+            keys
+        )
+        .execute(conn)
+        .await?;
+
+        if result.rows_affected() > 0 {
+            debug!("Released {} expired unique key(s)", result.rows_affected());
+        }
+
+        Ok(())
+    }
+
+    /// Find a job by its ID within a tenant
+    #[instrument(name = "JobRepository::find_by_id", skip(pool))]
+    pub async fn find_by_id(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        job_id: i32,
+    ) -> Result<Option<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
+            FROM jobs
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+            job_id,
+            tenant_id
+        )
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// List every recorded status transition of a tenant's job, oldest first
+    #[instrument(name = "JobRepository::list_events", skip(pool))]
+    pub async fn list_events(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        job_id: i32,
+    ) -> Result<Vec<JobEventRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobEventRow,
+            r#"
+            SELECT e.id, e.job_id, e.from_status, e.to_status, e.worker_id, e.attempt, e.error, e.created_at
+            FROM job_events e
+            JOIN jobs j ON j.id = e.job_id
+            WHERE e.job_id = $1 AND j.tenant_id = $2
+            ORDER BY e.id ASC
+            "#,
+            job_id,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// List a tenant's status transitions recorded after `after_id`, oldest first
+    ///
+    /// Used to replay events a stream client missed (`Last-Event-ID`). Optional
+    /// filters narrow to one job, job name or target status.
+    #[instrument(name = "JobRepository::list_events_after", skip(pool))]
+    pub async fn list_events_after(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        after_id: i64,
+        job_id: Option<i32>,
+        name: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<JobEventMessage>, sqlx::Error> {
+        sqlx::query_as!(
+            JobEventMessage,
+            r#"
+            SELECT e.id, e.job_id, j.tenant_id, j.name, e.from_status, e.to_status, e.worker_id, e.attempt, e.error, e.created_at
+            FROM job_events e
+            JOIN jobs j ON j.id = e.job_id
+            WHERE j.tenant_id = $1
+              AND e.id > $2
+              AND ($3::INT IS NULL OR e.job_id = $3)
+              AND ($4::VARCHAR IS NULL OR j.name = $4)
+              AND ($5::VARCHAR IS NULL OR e.to_status = $5)
+            ORDER BY e.id ASC
+            LIMIT $6
+            "#,
+            tenant_id,
+            after_id,
+            job_id,
+            name,
+            status,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// List the events of a tenant's `job_ids` recorded after `after_id`, oldest first
+    #[instrument(name = "JobRepository::list_events_for_jobs", skip(pool, job_ids), fields(jobs = job_ids.len()))]
+    pub async fn list_events_for_jobs(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        job_ids: &[i32],
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<JobEventMessage>, sqlx::Error> {
+        sqlx::query_as!(
+            JobEventMessage,
+            r#"
+            SELECT e.id, e.job_id, j.tenant_id, j.name, e.from_status, e.to_status, e.worker_id, e.attempt, e.error, e.created_at
+            FROM job_events e
+            JOIN jobs j ON j.id = e.job_id
+            WHERE j.tenant_id = $1
+              AND e.job_id = ANY($2)
+              AND e.id > $3
+            ORDER BY e.id ASC
+            LIMIT $4
+            "#,
+            tenant_id,
+            job_ids,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Id of the most recent job event, or 0 if none were recorded
+    pub async fn latest_event_id(pool: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar!(r#"SELECT COALESCE(MAX(id), 0) AS "id!" FROM job_events"#)
+            .fetch_one(pool)
+            .await
+    }
+
+    /// List a tenant's jobs created by one bulk upload, in creation order
+    #[instrument(name = "JobRepository::list_by_batch", skip(pool))]
+    pub async fn list_by_batch(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        batch_id: Uuid,
+    ) -> Result<Vec<JobRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobRow,
+            r#"
+            SELECT id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
+            FROM jobs
+            WHERE tenant_id = $1 AND batch_id = $2
+            ORDER BY id ASC
+            "#,
+            tenant_id,
+            batch_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Delete the given jobs along with their events
+    #[instrument(name = "JobRepository::delete_by_ids", skip_all, fields(count = ids.len()))]
+    pub async fn delete_by_ids(
+        pool: &Pool<Postgres>,
+        ids: &[i32],
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query!("DELETE FROM jobs WHERE id = ANY($1)", ids)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Count jobs grouped by status
+    ///
+    /// Reads the trigger-maintained `job_counts` rather than scanning `jobs`.
+    #[instrument(name = "JobRepository::count_by_status", skip_all)]
+    pub async fn count_by_status(
+        pool: &Pool<Postgres>,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT status, SUM(count)::BIGINT AS "count!"
+            FROM job_counts
+            GROUP BY status
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.status, row.count)).collect())
     }
 
     /// Acquire the next available job with row-level locking
@@ -81,21 +723,33 @@ impl JobRepository {
     /// Uses PostgreSQL's FOR UPDATE SKIP LOCKED to prevent race conditions between workers.
     ///
     /// # How it works
-    /// - Selects one 'new' job (oldest first - FIFO)
-    /// - Locks the row with FOR UPDATE SKIP LOCKED
-    /// - If another worker already locked it, skips to next available job
-    /// - Updates status to 'processing'
-    /// - Returns the job
+    /// - Considers only jobs named in `queues`, or every job if it is empty
+    /// - Considers only tenants below their `max_concurrent_jobs`
+    /// - Takes the oldest 'new' job of each tenant's queued names, skipping jobs
+    ///   whose retry backoff has not elapsed yet. Names come from `job_counts`, so
+    ///   the tenant's paused queues are dropped without reading their jobs
+    /// - Picks the tenant with the fewest jobs currently processing, so one
+    ///   tenant's backlog cannot starve the others; ties go to the oldest job.
+    ///   Processing counts come from `job_counts`, and nothing is locked yet
+    /// - Locks only the picked name's oldest unlocked job with FOR UPDATE SKIP LOCKED
+    /// - If other workers hold all of that name's jobs, picks again without it
+    /// - Updates status to 'processing', increments the attempt counter and
+    ///   records the acquiring worker's registry id in `locked_by`
+    /// - Releases the job's unique key if it is only held while queued
+    /// - Records the transition in job_events
+    /// - Returns the job with the time it became runnable: its last move into 'new'
+    ///   (creation, release from 'blocked' or a retry) or its retry `run_at`,
+    ///   whichever is later
     ///
     /// # Returns
-    /// - `Ok(Some(job))` - Successfully acquired a job
+    /// - `Ok(Some((job, ready_at)))` - Successfully acquired a job
     /// - `Ok(None)` - No jobs available (all are processing/completed/failed)
     /// - `Err(e)` - Database error
     ///
     /// # Example
     /// ```rust
-    /// match JobRepository::acquire_next_job(&pool).await {
-    ///     Ok(Some(job)) => {
+    /// match JobRepository::acquire_next_job(&pool, worker_id, Some(registration_id), &[]).await {
+    ///     Ok(Some((job, _ready_at))) => {
     ///         // Process the job...
     ///         println!("Acquired job: {}", job.id);
     ///     }
@@ -107,103 +761,1107 @@ impl JobRepository {
     ///     }
     /// }
     /// ```
+    #[instrument(name = "JobRepository::acquire_next_job", skip(pool))]
     pub async fn acquire_next_job(
         pool: &Pool<Postgres>,
-    ) -> Result<Option<JobRow>, sqlx::Error> {
+        worker_id: u32,
+        locked_by: Option<Uuid>,
+        queues: &[String],
+    ) -> Result<Option<(JobRow, NaiveDateTime)>, sqlx::Error> {
         debug!("Attempting to acquire next available job");
 
         // Start a transaction
         let mut tx = pool.begin().await?;
 
-        // Select and lock one 'new' job (oldest first)
-        // FOR UPDATE locks the row
-        // SKIP LOCKED skips rows already locked by other workers
-        let job_row = sqlx::query(
-            r#"
-            SELECT id, name, status, created_at, updated_at
-            FROM jobs
-            WHERE status = 'new'
-            ORDER BY created_at ASC
-            LIMIT 1
-            FOR UPDATE SKIP LOCKED
-            "#
-        )
-        .fetch_optional(&mut *tx)
-        .await?;
+        // Pick a tenant and job name, then lock that name's oldest runnable job.
+        // Candidate names come from job_counts, so paused names and names outside
+        // `queues` are dropped before any job row is read; each remaining name's head
+        // is one probe of its queued-jobs index. Picking does not lock, so concurrent
+        // workers never hold other tenants' head jobs while choosing.
+        // The concurrency quota is checked without locking the tenant, so concurrent
+        // acquirers can briefly overshoot it by at most the number of workers
+        let mut exhausted_tenants: Vec<String> = Vec::new();
+        let mut exhausted_names: Vec<String> = Vec::new();
+        let (job_id, tenant_id) = loop {
+            let picked: Option<(String, String)> = sqlx::query_as(
+                r#"
+                WITH running AS (
+                    SELECT tenant_id, SUM(count) AS processing
+                    FROM job_counts
+                    WHERE status = 'processing'
+                    GROUP BY tenant_id
+                ),
+                queued AS (
+                    SELECT c.tenant_id, c.name
+                    FROM job_counts c
+                    WHERE c.status = 'new'
+                      AND (cardinality($3::TEXT[]) = 0 OR c.name = ANY($3))
+                      AND NOT EXISTS (
+                          SELECT 1 FROM paused_queues p
+                          WHERE p.tenant_id = c.tenant_id AND p.name = c.name
+                      )
+                      AND NOT EXISTS (
+                          SELECT 1 FROM UNNEST($1::TEXT[], $2::TEXT[]) AS x(tenant_id, name)
+                          WHERE x.tenant_id = c.tenant_id AND x.name = c.name
+                      )
+                    GROUP BY c.tenant_id, c.name
+                    HAVING SUM(c.count) > 0
+                )
+                SELECT q.tenant_id, q.name
+                FROM queued q
+                JOIN tenants t ON t.id = q.tenant_id
+                LEFT JOIN running r ON r.tenant_id = q.tenant_id
+                CROSS JOIN LATERAL (
+                    SELECT j.created_at
+                    FROM jobs j
+                    WHERE j.tenant_id = q.tenant_id AND j.name = q.name AND j.status = 'new'
+                      AND (j.run_at IS NULL OR j.run_at <= NOW())
+                    ORDER BY j.created_at ASC
+                    LIMIT 1
+                ) head
+                WHERE t.max_concurrent_jobs IS NULL OR COALESCE(r.processing, 0) < t.max_concurrent_jobs
+                ORDER BY COALESCE(r.processing, 0) ASC, head.created_at ASC
+                LIMIT 1
+                "#
+            )
+            .bind(&exhausted_tenants)
+            .bind(&exhausted_names)
+            .bind(queues)
+            .fetch_optional(&mut *tx)
+            .await?;
 
-        // If no job found, return None
-        let job_row = match job_row {
-            Some(row) => row,
-            None => {
+            // If no tenant has a runnable job, return None
+            let Some((tenant_id, name)) = picked else {
                 debug!("No jobs available to acquire");
                 tx.rollback().await?;
                 return Ok(None);
+            };
+
+            // FOR UPDATE locks the row
+            // SKIP LOCKED moves past jobs of this name already locked by other workers
+            // The paused check is repeated in case the name was paused after picking
+            let job_id: Option<i32> = sqlx::query_scalar(
+                r#"
+                SELECT j.id
+                FROM jobs j
+                WHERE j.tenant_id = $1 AND j.name = $2 AND j.status = 'new'
+                  AND (j.run_at IS NULL OR j.run_at <= NOW())
+                  AND NOT EXISTS (
+                      SELECT 1 FROM paused_queues p
+                      WHERE p.tenant_id = j.tenant_id AND p.name = j.name
+                  )
+                ORDER BY j.created_at ASC
+                LIMIT 1
+                FOR UPDATE OF j SKIP LOCKED
+                "#
+            )
+            .bind(&tenant_id)
+            .bind(&name)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            match job_id {
+                Some(job_id) => break (job_id, tenant_id),
+                None => {
+                    debug!("Every runnable {} job of tenant {} is taken, picking again", name, tenant_id);
+                    exhausted_tenants.push(tenant_id);
+                    exhausted_names.push(name);
+                }
             }
         };
 
-        // Extract job ID
-        let job_id: i32 = job_row.try_get("id")?;
-
-        info!("Acquired job with id={}, updating status to 'processing'", job_id);
+        info!("Acquired job with id={} (tenant={}), updating status to 'processing'", job_id, tenant_id);
 
         // Update the job status to 'processing'
         let updated_job = sqlx::query_as!(
             JobRow,
             r#"
             UPDATE jobs
-            SET status = 'processing'
+            SET status = 'processing',
+                attempts = attempts + 1,
+                locked_by = $2,
+                unique_lock = CASE WHEN unique_scope = 'queued' THEN NULL ELSE unique_lock END
             WHERE id = $1
-            RETURNING id, name, status, created_at, updated_at
+            RETURNING id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
             "#,
-            job_id
+            job_id,
+            locked_by
         )
         .fetch_one(&mut *tx)
         .await?;
 
+        let ready_at = sqlx::query_scalar!(
+            r#"
+            SELECT GREATEST(
+                $2::TIMESTAMP,
+                (SELECT MAX(e.created_at) FROM job_events e WHERE e.job_id = $1 AND e.to_status = 'new')
+            )
+            "#,
+            updated_job.id,
+            updated_job.run_at
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .unwrap_or(updated_job.created_at);
+
+        Self::record_event(
+            &mut tx,
+            updated_job.id,
+            Some("new"),
+            &updated_job.status,
+            Some(worker_id),
+            updated_job.attempts,
+            None,
+        )
+        .await?;
+
         // Commit the transaction
         tx.commit().await?;
 
         info!("Successfully acquired and locked job: id={}, name={}", updated_job.id, updated_job.name);
 
-        Ok(Some(updated_job))
+        Ok(Some((updated_job, ready_at)))
     }
 
     /// Update job status
     ///
-    /// Updates the status of a job by its ID.
+    /// Updates the status of a job by its ID and records the transition in job_events
+    /// within the same transaction.
+    /// A held unique key is released once the new status is outside its scope;
+    /// window-scoped keys are only released when the window expires.
+    /// A failed attempt below the job's `max_attempts` goes back to 'new' instead,
+    /// with `run_at` delayed by `retry_backoff_secs` doubled for every earlier attempt
+    /// (capped at a day). Steps of a cancelled workflow are not retried. A retried
+    /// 'queued'-scope job takes its unique key again; if another job holds it by
+    /// then, the failure is final instead.
     /// The updated_at timestamp is automatically updated by the database trigger.
     ///
     /// # Arguments
     /// - `pool` - Database connection pool
     /// - `job_id` - ID of the job to update
-    /// - `status` - New status value ("processing", "success", "failed")
+    /// - `status` - New status value ("processing", "success", "failed"); a retried
+    ///   failure is stored as "new"
+    /// - `worker_id` - Worker that performed the transition, if any
+    /// - `error` - Failure reason to store with the event, if any
     ///
     /// # Returns
     /// - `Ok(JobRow)` - Updated job
     /// - `Err(sqlx::Error)` - Database error or job not found
+    #[instrument(name = "JobRepository::update_job_status", skip(pool))]
     pub async fn update_job_status(
         pool: &Pool<Postgres>,
         job_id: i32,
         status: &str,
+        worker_id: Option<u32>,
+        error: Option<&str>,
+    ) -> Result<JobRow, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let updated_job = Self::update_status_in(&mut tx, job_id, status, worker_id, error).await?;
+        tx.commit().await?;
+
+        Ok(updated_job)
+    }
+
+    /// Complete a job with its handler's result and the jobs it enqueued, in one transaction
+    ///
+    /// Children are queued with the parent's tenant, trace context and request id, and
+    /// do not count against the queued-job quota (the parent already did). The join
+    /// job, if any, depends on every child with `run_anyway`, so it is released once
+    /// all of them reach a terminal status; its object payload gets the child ids
+    /// under `children`.
+    ///
+    /// # Returns
+    /// - `Ok(JobRow)` - The parent after its transition to 'success'
+    /// - `Err(InsertError)` - Database error; nothing was written
+    #[instrument(name = "JobRepository::complete_job", skip(pool, spawned, result), fields(children = spawned.children.len(), join = spawned.join.is_some()))]
+    pub async fn complete_job(
+        pool: &Pool<Postgres>,
+        job_id: i32,
+        worker_id: Option<u32>,
+        spawned: &SpawnedJobs,
+        result: Option<&serde_json::Value>,
+    ) -> Result<JobRow, InsertError> {
+        let mut tx = pool.begin().await?;
+
+        // Stored before the transition so the completion callback carries it
+        if let Some(result) = result {
+            sqlx::query!("UPDATE jobs SET result = $2 WHERE id = $1", job_id, result)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let child_ids = Self::insert_spawned(&mut tx, job_id, &spawned.children).await?;
+
+        if let Some(join) = &spawned.join {
+            let mut payload = join.payload.clone().unwrap_or_else(|| serde_json::json!({}));
+            if let Some(object) = payload.as_object_mut() {
+                object.insert("children".to_string(), serde_json::json!(child_ids));
+            }
+
+            let join_ids = Self::insert_spawned(
+                &mut tx,
+                job_id,
+                &[SpawnedJob { name: join.name.clone(), payload: Some(payload) }],
+            )
+            .await?;
+            if !child_ids.is_empty() {
+                let tenant_id = sqlx::query_scalar!("SELECT tenant_id FROM jobs WHERE id = $1", job_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+                Self::attach_dependencies(&mut tx, &tenant_id, join_ids[0], &child_ids, ParentFailure::RunAnyway)
+                    .await?;
+            }
+        }
+
+        let updated_job = Self::update_status_in(&mut tx, job_id, "success", worker_id, None).await?;
+
+        tx.commit().await?;
+
+        info!(job_id, children = child_ids.len(), "Completed job with handler output");
+        Ok(updated_job)
+    }
+
+    /// Insert jobs spawned by a handler as 'new', copying the parent's tenant and trace
+    async fn insert_spawned(
+        conn: &mut PgConnection,
+        parent_id: i32,
+        jobs: &[SpawnedJob],
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        if jobs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let names: Vec<&str> = jobs.iter().map(|job| job.name.as_str()).collect();
+        let payloads: Vec<Option<serde_json::Value>> = jobs.iter().map(|job| job.payload.clone()).collect();
+
+        sqlx::query_scalar(
+            r#"
+            WITH parent AS (
+                SELECT tenant_id, trace_context, request_id FROM jobs WHERE id = $1
+            ), inserted AS (
+                INSERT INTO jobs (tenant_id, name, status, trace_context, request_id, payload)
+                SELECT parent.tenant_id, c.name, 'new', parent.trace_context, parent.request_id, c.payload
+                FROM parent, UNNEST($2::VARCHAR[], $3::JSONB[]) WITH ORDINALITY AS c(name, payload, ordinal)
+                ORDER BY c.ordinal
+                RETURNING id, status
+            ), events AS (
+                INSERT INTO job_events (job_id, to_status)
+                SELECT id, status FROM inserted
+            )
+            SELECT id FROM inserted ORDER BY id
+            "#,
+        )
+        .bind(parent_id)
+        .bind(&names)
+        .bind(&payloads)
+        .fetch_all(conn)
+        .await
+    }
+
+    /// Apply a status transition on the caller's transaction
+    ///
+    /// See `update_job_status`; also used by `complete_job` so the result,
+    /// children and the parent's completion commit together.
+    async fn update_status_in(
+        conn: &mut PgConnection,
+        job_id: i32,
+        status: &str,
+        worker_id: Option<u32>,
+        error: Option<&str>,
     ) -> Result<JobRow, sqlx::Error> {
         debug!("Updating job {} to status: {}", job_id, status);
 
+        // Lock the row so the recorded from_status matches what we overwrite
+        let current = sqlx::query!(
+            r#"
+            SELECT j.tenant_id, j.status, j.attempts, j.max_attempts, j.unique_key, j.unique_scope, j.unique_lock,
+                   EXISTS (
+                       SELECT 1 FROM workflows w WHERE w.id = j.workflow_id AND w.status = 'cancelled'
+                   ) AS "workflow_cancelled!"
+            FROM jobs j
+            WHERE j.id = $1
+            FOR UPDATE OF j
+            "#,
+            job_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let mut retry = status == "failed" && current.attempts < current.max_attempts && !current.workflow_cancelled;
+
+        // A 'queued' key was given up at acquisition; the retry queues the job again, so it
+        // takes the key back. If another job has taken it meanwhile, this attempt is final.
+        let mut error = error.map(str::to_string);
+        if let (true, Some(key), None) = (retry, &current.unique_key, &current.unique_lock) {
+            let holder = sqlx::query_scalar!(
+                "SELECT id FROM jobs WHERE tenant_id = $1 AND unique_lock = $2",
+                current.tenant_id,
+                key
+            )
+            .fetch_optional(&mut *conn)
+            .await?;
+
+            if let Some(holder) = holder {
+                warn!(job_id, holder, "Not retrying job: its unique key is held by another job");
+                retry = false;
+                error = Some(format!(
+                    "{}; not retried because unique key {} is held by job {}",
+                    error.as_deref().unwrap_or("Job failed"),
+                    key,
+                    holder
+                ));
+            }
+        }
+        let status = if retry { "new" } else { status };
+
         let updated_job = sqlx::query_as!(
             JobRow,
             r#"
             UPDATE jobs
-            SET status = $1
+            SET status = $1,
+                run_at = CASE
+                    WHEN $3 THEN NOW() + make_interval(secs => LEAST(retry_backoff_secs * POWER(2, attempts - 1), 86400))
+                    ELSE run_at
+                END,
+                unique_lock = CASE
+                    WHEN unique_scope = 'window' THEN unique_lock
+                    WHEN $1::VARCHAR = 'new' THEN COALESCE(unique_lock, unique_key)
+                    WHEN $1::VARCHAR = 'processing' AND unique_scope = 'queued_or_running' THEN unique_lock
+                    ELSE NULL
+                END
             WHERE id = $2
-            RETURNING id, name, status, created_at, updated_at
+            RETURNING id, tenant_id, name, status, attempts, created_at, updated_at, trace_context, request_id, idempotency_key, unique_key, unique_scope, unique_until, payload, batch_id, on_parent_failure, workflow_id, workflow_step, max_attempts, retry_backoff_secs, run_at, callback_url, result
             "#,
             status,
-            job_id
+            job_id,
+            retry
         )
-        .fetch_one(pool)
+        .fetch_one(&mut *conn)
         .await?;
 
+        Self::record_event(
+            &mut *conn,
+            job_id,
+            Some(&current.status),
+            status,
+            worker_id,
+            updated_job.attempts,
+            error.as_deref(),
+        )
+        .await?;
+
+        if retry {
+            info!(
+                job_id,
+                attempt = updated_job.attempts,
+                max_attempts = updated_job.max_attempts,
+                "Job failed; retrying at {:?}",
+                updated_job.run_at
+            );
+        }
+
+        if matches!(status, "success" | "failed" | "cancelled") {
+            // Release or cancel waiting children in the same transaction as the parent
+            let mut finished = FinishedJobs::default();
+            finished.add(job_id, status == "success", updated_job.batch_id, updated_job.workflow_id);
+            Self::release_children(&mut *conn, job_id, &mut finished).await?;
+
+            // Finishing the last member of a batch or workflow settles it, and callbacks
+            // are queued, in this transaction
+            finished.finish(&mut *conn).await?;
+        }
+
         debug!("Job {} status updated to: {}", job_id, status);
 
         Ok(updated_job)
     }
+
+    /// Record a freshly inserted job's parents and move it out of `new` if it cannot run yet
+    ///
+    /// Parents are locked `FOR SHARE` so none of them can finish between reading its
+    /// status here and the edge becoming visible to `release_children`.
+    ///
+    /// # Returns
+    /// - `Ok(None)` - Every parent allows the job to run; it stays `new`
+    /// - `Ok(Some("blocked"))` - Some parent is still pending
+    /// - `Ok(Some("cancelled"))` - A parent failed and the job's policy is `cancel`
+    pub(crate) async fn attach_dependencies(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        job_id: i32,
+        depends_on: &[i32],
+        on_parent_failure: ParentFailure,
+    ) -> Result<Option<&'static str>, InsertError> {
+        let mut parent_ids = depends_on.to_vec();
+        parent_ids.sort_unstable();
+        parent_ids.dedup();
+
+        let parents = sqlx::query!(
+            r#"
+            SELECT id, status
+            FROM jobs
+            WHERE tenant_id = $1 AND id = ANY($2)
+            ORDER BY id
+            FOR SHARE
+            "#,
+            tenant_id,
+            &parent_ids
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        if parents.len() != parent_ids.len() {
+            let missing = parent_ids
+                .into_iter()
+                .filter(|id| !parents.iter().any(|p| p.id == *id))
+                .collect();
+            return Err(InsertError::UnknownParents(missing));
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT INTO job_dependencies (job_id, depends_on_id)
+            SELECT $1, UNNEST($2::INT[])
+            "#,
+            job_id,
+            &parent_ids
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Self::ensure_acyclic(&mut *conn, job_id).await?;
+
+        let is_failed = |status: &str| matches!(status, "failed" | "cancelled");
+        let all_succeeded = parents.iter().all(|p| p.status == "success");
+        let any_failed = parents.iter().any(|p| is_failed(&p.status));
+        let all_finished = parents.iter().all(|p| p.status == "success" || is_failed(&p.status));
+
+        let next_status = if all_succeeded {
+            None
+        } else if any_failed && on_parent_failure == ParentFailure::Cancel {
+            Some("cancelled")
+        } else if all_finished {
+            None
+        } else {
+            Some("blocked")
+        };
+
+        if let Some(next_status) = next_status {
+            sqlx::query!(
+                r#"
+                UPDATE jobs
+                SET status = $2,
+                    unique_lock = CASE WHEN $2::VARCHAR = 'cancelled' AND unique_scope <> 'window' THEN NULL ELSE unique_lock END
+                WHERE id = $1
+                "#,
+                job_id,
+                next_status
+            )
+            .execute(&mut *conn)
+            .await?;
+
+            let error = (next_status == "cancelled").then_some("Parent job failed");
+            Self::record_event(&mut *conn, job_id, Some("new"), next_status, None, 0, error).await?;
+        }
+
+        debug!("Job {} depends on {:?} ({:?})", job_id, parent_ids, next_status);
+        Ok(next_status)
+    }
+
+    /// Reject the job if it is reachable from its own parents
+    async fn ensure_acyclic(conn: &mut PgConnection, job_id: i32) -> Result<(), InsertError> {
+        let cycle = sqlx::query_scalar!(
+            r#"
+            WITH RECURSIVE ancestors(id) AS (
+                SELECT depends_on_id FROM job_dependencies WHERE job_id = $1
+                UNION
+                SELECT d.depends_on_id
+                FROM job_dependencies d
+                JOIN ancestors a ON d.job_id = a.id
+            )
+            SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $1) AS "cycle!"
+            "#,
+            job_id
+        )
+        .fetch_one(conn)
+        .await?;
+
+        if cycle {
+            return Err(InsertError::DependencyCycle(job_id));
+        }
+
+        Ok(())
+    }
+
+    /// Update the waiting children of a job that just finished
+    ///
+    /// - Children with a failed or cancelled parent and the `cancel` policy are
+    ///   cancelled, and their own children are processed in turn
+    /// - Children whose parents all succeeded (or, for `run_anyway`, all finished)
+    ///   move to `new` so workers can pick them up
+    ///
+    /// Children are locked before their parents are inspected. When two parents of
+    /// the same child finish concurrently, the second transaction waits for the
+    /// first to commit and then sees both parents finished, so the child is
+    /// always released.
+    ///
+    /// Cancelled children are added to `finished`, since their batches and workflows
+    /// may now be finished and their callbacks are due.
+    async fn release_children(
+        conn: &mut PgConnection,
+        job_id: i32,
+        finished: &mut FinishedJobs,
+    ) -> Result<(), sqlx::Error> {
+        let mut parents = vec![job_id];
+
+        while let Some(parent_id) = parents.pop() {
+            let children = sqlx::query_scalar!(
+                r#"
+                SELECT j.id
+                FROM jobs j
+                JOIN job_dependencies d ON d.job_id = j.id
+                WHERE d.depends_on_id = $1 AND j.status = 'blocked'
+                ORDER BY j.id
+                FOR UPDATE OF j
+                "#,
+                parent_id
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+
+            if children.is_empty() {
+                continue;
+            }
+
+            let cancelled = sqlx::query!(
+                r#"
+                UPDATE jobs c
+                SET status = 'cancelled',
+                    unique_lock = CASE WHEN c.unique_scope = 'window' THEN c.unique_lock ELSE NULL END
+                WHERE c.id = ANY($1)
+                  AND c.status = 'blocked'
+                  AND c.on_parent_failure = 'cancel'
+                  AND EXISTS (
+                      SELECT 1
+                      FROM job_dependencies d
+                      JOIN jobs p ON p.id = d.depends_on_id
+                      WHERE d.job_id = c.id AND p.status IN ('failed', 'cancelled')
+                  )
+                RETURNING c.id, c.attempts, c.batch_id, c.workflow_id
+                "#,
+                &children
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+
+            for child in cancelled {
+                Self::record_event(&mut *conn, child.id, Some("blocked"), "cancelled", None, child.attempts, Some("Parent job failed")).await?;
+                info!(job_id = child.id, parent_id, "Cancelled job after parent failure");
+                parents.push(child.id);
+                finished.add(child.id, false, child.batch_id, child.workflow_id);
+            }
+
+            let released = sqlx::query!(
+                r#"
+                UPDATE jobs c
+                SET status = 'new'
+                WHERE c.id = ANY($1)
+                  AND c.status = 'blocked'
+                  AND NOT EXISTS (
+                      SELECT 1
+                      FROM job_dependencies d
+                      JOIN jobs p ON p.id = d.depends_on_id
+                      WHERE d.job_id = c.id
+                        AND (p.status NOT IN ('success', 'failed', 'cancelled')
+                             OR (p.status <> 'success' AND c.on_parent_failure = 'cancel'))
+                  )
+                RETURNING c.id, c.attempts
+                "#,
+                &children
+            )
+            .fetch_all(&mut *conn)
+            .await?;
+
+            for child in released {
+                Self::record_event(&mut *conn, child.id, Some("blocked"), "new", None, child.attempts, None).await?;
+                info!(job_id = child.id, parent_id, "Released job after its parents finished");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ensure `adding` more queued jobs fit in the tenant's `max_queued_jobs`
+    ///
+    /// Locks the tenant row so concurrent submissions for the same tenant are
+    /// serialized and cannot both pass the check. `FOR NO KEY UPDATE` does not
+    /// conflict with the foreign key checks of job inserts.
+    pub(crate) async fn check_queue_quota(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        adding: i64,
+    ) -> Result<(), InsertError> {
+        let limit = sqlx::query_scalar!(
+            "SELECT max_queued_jobs FROM tenants WHERE id = $1 FOR NO KEY UPDATE",
+            tenant_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        let Some(limit) = limit else {
+            return Ok(());
+        };
+
+        let queued = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM jobs WHERE tenant_id = $1 AND status = 'new'"#,
+            tenant_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if queued + adding > limit as i64 {
+            warn!("Tenant {} queued-job quota exceeded: {} + {} > {}", tenant_id, queued, adding, limit);
+            return Err(InsertError::QuotaExceeded { limit: limit as i64, queued });
+        }
+
+        Ok(())
+    }
+
+    /// Record a status transition in job_events
+    ///
+    /// Always called on the connection of the transaction that changes the job,
+    /// so the event log can never disagree with the job's status.
+    pub(crate) async fn record_event(
+        conn: &mut PgConnection,
+        job_id: i32,
+        from_status: Option<&str>,
+        to_status: &str,
+        worker_id: Option<u32>,
+        attempt: i32,
+        error: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO job_events (job_id, from_status, to_status, worker_id, attempt, error)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            job_id,
+            from_status,
+            to_status,
+            worker_id.map(|id| id as i32),
+            attempt,
+            error
+        )
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Jobs that reached a terminal status in one transaction, with their batches and workflows
+#[derive(Default)]
+struct FinishedJobs {
+    jobs: Vec<i32>,
+    /// Batch of each finished member and whether it succeeded
+    batches: Vec<(Uuid, bool)>,
+    workflows: Vec<Uuid>,
+}
+
+impl FinishedJobs {
+    fn add(&mut self, job_id: i32, succeeded: bool, batch_id: Option<Uuid>, workflow_id: Option<Uuid>) {
+        self.jobs.push(job_id);
+        self.batches.extend(batch_id.map(|batch_id| (batch_id, succeeded)));
+        self.workflows.extend(workflow_id);
+    }
+
+    /// Queue the jobs' callbacks and settle every group none of whose members are pending any more
+    async fn finish(mut self, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+        WebhookRepository::enqueue_for_jobs(&mut *conn, &self.jobs).await?;
+
+        self.batches.sort();
+        self.workflows.sort();
+        self.workflows.dedup();
+
+        for members in self.batches.chunk_by(|a, b| a.0 == b.0) {
+            let succeeded = members.iter().filter(|(_, succeeded)| *succeeded).count() as i32;
+            let failed = members.len() as i32 - succeeded;
+            BatchRepository::on_member_finished(&mut *conn, members[0].0, succeeded, failed).await?;
+        }
+        for workflow_id in self.workflows {
+            WorkflowRepository::on_step_finished(&mut *conn, workflow_id).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::queue_repository::QueueRepository;
+    use crate::db::tenant_repository::TenantRepository;
+
+    async fn insert_job(pool: &Pool<Postgres>, name: &str) -> i32 {
+        sqlx::query_scalar("INSERT INTO jobs (name, status) VALUES ($1, 'new') RETURNING id")
+            .bind(name)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// Add the edges `job -> parents` in a transaction that is rolled back afterwards
+    async fn try_attach(pool: &Pool<Postgres>, job_id: i32, parents: &[i32]) -> Result<(), InsertError> {
+        let mut tx = pool.begin().await.unwrap();
+        let attached =
+            JobRepository::attach_dependencies(&mut tx, "default", job_id, parents, ParentFailure::default()).await;
+        tx.rollback().await.unwrap();
+        attached.map(|_| ())
+    }
+
+    fn job(spec: serde_json::Value) -> Job {
+        let mut spec = spec;
+        spec["status"] = serde_json::json!("new");
+        serde_json::from_value(spec).unwrap()
+    }
+
+    fn context(tenant_id: &str) -> EnqueueContext {
+        EnqueueContext {
+            tenant_id: tenant_id.to_string(),
+            trace_context: None,
+            request_id: None,
+            idempotency_retention_hours: 24,
+            batch_id: None,
+            api_key_id: None,
+        }
+    }
+
+    async fn add_tenant(pool: &Pool<Postgres>, id: &str, max_concurrent: Option<i32>, max_queued: Option<i32>) {
+        TenantRepository::upsert(pool, id, max_concurrent, max_queued).await.unwrap();
+    }
+
+    /// Create a job named `name` for `tenant_id` and return its id
+    async fn enqueue(pool: &Pool<Postgres>, tenant_id: &str, name: &str) -> i32 {
+        let (row, created) = JobRepository::create(pool, &job(serde_json::json!({ "name": name })), &context(tenant_id))
+            .await
+            .unwrap();
+        assert!(created);
+        row.id
+    }
+
+    async fn acquire(pool: &Pool<Postgres>) -> Option<i32> {
+        JobRepository::acquire_next_job(pool, 1, None, &[]).await.unwrap().map(|(job, _)| job.id)
+    }
+
+    #[sqlx::test]
+    async fn ensure_acyclic_rejects_edges_closing_a_cycle(pool: Pool<Postgres>) {
+        let extract = insert_job(&pool, "extract").await;
+        let load = insert_job(&pool, "load").await;
+        let report = insert_job(&pool, "report").await;
+
+        // extract <- load <- report
+        sqlx::query("INSERT INTO job_dependencies (job_id, depends_on_id) VALUES ($1, $2), ($3, $1)")
+            .bind(load)
+            .bind(extract)
+            .bind(report)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            try_attach(&pool, extract, &[report]).await,
+            Err(InsertError::DependencyCycle(id)) if id == extract
+        ));
+        assert!(matches!(
+            try_attach(&pool, load, &[report]).await,
+            Err(InsertError::DependencyCycle(id)) if id == load
+        ));
+    }
+
+    #[sqlx::test]
+    async fn ensure_acyclic_allows_shared_ancestors(pool: Pool<Postgres>) {
+        let root = insert_job(&pool, "root").await;
+        let left = insert_job(&pool, "left").await;
+        let right = insert_job(&pool, "right").await;
+        let join = insert_job(&pool, "join").await;
+
+        sqlx::query("INSERT INTO job_dependencies (job_id, depends_on_id) VALUES ($1, $3), ($2, $3)")
+            .bind(left)
+            .bind(right)
+            .bind(root)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // A diamond reaches root twice but never join itself
+        assert!(try_attach(&pool, join, &[left, right, root]).await.is_ok());
+    }
+
+    #[sqlx::test]
+    async fn acquire_prefers_the_tenant_with_fewest_running_jobs(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, None).await;
+        add_tenant(&pool, "globex", None, None).await;
+        let acme_first = enqueue(&pool, "acme", "report").await;
+        let acme_second = enqueue(&pool, "acme", "report").await;
+        let globex = enqueue(&pool, "globex", "report").await;
+
+        // Ties go to the oldest job; after that globex runs nothing and goes first
+        assert_eq!(acquire(&pool).await, Some(acme_first));
+        assert_eq!(acquire(&pool).await, Some(globex));
+        assert_eq!(acquire(&pool).await, Some(acme_second));
+        assert_eq!(acquire(&pool).await, None);
+    }
+
+    #[sqlx::test]
+    async fn acquire_respects_max_concurrent_jobs(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", Some(1), None).await;
+        let first = enqueue(&pool, "acme", "report").await;
+        let second = enqueue(&pool, "acme", "report").await;
+
+        assert_eq!(acquire(&pool).await, Some(first));
+        assert_eq!(acquire(&pool).await, None);
+
+        JobRepository::update_job_status(&pool, first, "success", Some(1), None).await.unwrap();
+        assert_eq!(acquire(&pool).await, Some(second));
+    }
+
+    #[sqlx::test]
+    async fn acquire_moves_on_when_a_tenants_jobs_are_locked(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, None).await;
+        add_tenant(&pool, "globex", None, None).await;
+        let acme = enqueue(&pool, "acme", "report").await;
+        let globex = enqueue(&pool, "globex", "report").await;
+
+        // Another worker holds acme's only job, so acme is picked, found exhausted and skipped
+        let mut other = pool.begin().await.unwrap();
+        sqlx::query("SELECT id FROM jobs WHERE id = $1 FOR UPDATE")
+            .bind(acme)
+            .execute(&mut *other)
+            .await
+            .unwrap();
+
+        assert_eq!(acquire(&pool).await, Some(globex));
+        assert_eq!(acquire(&pool).await, None);
+
+        other.rollback().await.unwrap();
+        assert_eq!(acquire(&pool).await, Some(acme));
+    }
+
+    #[sqlx::test]
+    async fn create_enforces_the_queued_job_quota(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, Some(1)).await;
+        enqueue(&pool, "acme", "report").await;
+
+        let over = JobRepository::create(&pool, &job(serde_json::json!({ "name": "report" })), &context("acme")).await;
+        assert!(matches!(over, Err(InsertError::QuotaExceeded { limit: 1, queued: 1 })));
+
+        // Acquired jobs no longer count as queued
+        acquire(&pool).await.unwrap();
+        enqueue(&pool, "acme", "report").await;
+    }
+
+    #[sqlx::test]
+    async fn idempotency_key_replays_the_original_job(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, None).await;
+        add_tenant(&pool, "globex", None, None).await;
+        let spec = job(serde_json::json!({ "name": "charge", "idempotency_key": "order-7" }));
+
+        let (original, created) = JobRepository::create(&pool, &spec, &context("acme")).await.unwrap();
+        assert!(created);
+        let (replayed, created) = JobRepository::create(&pool, &spec, &context("acme")).await.unwrap();
+        assert!(!created);
+        assert_eq!(replayed.id, original.id);
+
+        // Keys are per tenant
+        let (other, created) = JobRepository::create(&pool, &spec, &context("globex")).await.unwrap();
+        assert!(created);
+        assert_ne!(other.id, original.id);
+
+        // Bulk uploads report the replay with the original id, including repeats within the file
+        let fresh = || job(serde_json::json!({ "name": "charge", "idempotency_key": "order-8" }));
+        let inserted = JobRepository::bulk_create(&pool, &[spec, fresh(), fresh()], &context("acme"))
+            .await
+            .unwrap();
+        assert_eq!(inserted.created.len(), 1);
+        let fresh_id = inserted.created[0].1;
+        assert_eq!(inserted.created[0].0, 1);
+        assert_eq!(inserted.duplicates, vec![(0, original.id), (2, fresh_id)]);
+    }
+
+    #[sqlx::test]
+    async fn idempotency_key_is_released_after_the_retention_window(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, None).await;
+        let spec = job(serde_json::json!({ "name": "charge", "idempotency_key": "order-7" }));
+        let (original, _) = JobRepository::create(&pool, &spec, &context("acme")).await.unwrap();
+
+        sqlx::query("UPDATE jobs SET created_at = NOW() - INTERVAL '25 hours' WHERE id = $1")
+            .bind(original.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let (reused, created) = JobRepository::create(&pool, &spec, &context("acme")).await.unwrap();
+        assert!(created);
+        assert_ne!(reused.id, original.id);
+
+        let old_key: Option<String> = sqlx::query_scalar("SELECT idempotency_key FROM jobs WHERE id = $1")
+            .bind(original.id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(old_key, None);
+    }
+
+    /// Create a job holding `unique_key` for `scope`, or return the job already holding it
+    async fn enqueue_unique(pool: &Pool<Postgres>, key: &str, scope: &str) -> (i32, bool) {
+        let spec = job(serde_json::json!({
+            "name": "sync",
+            "unique_key": key,
+            "unique_scope": scope,
+            "unique_window_secs": (scope == "window").then_some(3600),
+        }));
+        let (row, created) = JobRepository::create(pool, &spec, &context("default")).await.unwrap();
+        (row.id, created)
+    }
+
+    #[sqlx::test]
+    async fn unique_keys_are_held_for_their_scope(pool: Pool<Postgres>) {
+        // queued: released once a worker picks the job up
+        let (queued, _) = enqueue_unique(&pool, "q", "queued").await;
+        assert_eq!(enqueue_unique(&pool, "q", "queued").await, (queued, false));
+        assert_eq!(acquire(&pool).await, Some(queued));
+        assert!(enqueue_unique(&pool, "q", "queued").await.1);
+
+        // queued_or_running: released when the job finishes
+        let (running, _) = enqueue_unique(&pool, "r", "queued_or_running").await;
+        sqlx::query("UPDATE jobs SET status = 'processing' WHERE id = $1")
+            .bind(running)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(enqueue_unique(&pool, "r", "queued_or_running").await, (running, false));
+        JobRepository::update_job_status(&pool, running, "success", None, None).await.unwrap();
+        assert!(enqueue_unique(&pool, "r", "queued_or_running").await.1);
+
+        // window: held after the job finished, until the window ends
+        let (window, _) = enqueue_unique(&pool, "w", "window").await;
+        JobRepository::update_job_status(&pool, window, "success", None, None).await.unwrap();
+        assert_eq!(enqueue_unique(&pool, "w", "window").await, (window, false));
+        sqlx::query("UPDATE jobs SET unique_until = NOW() - INTERVAL '1 second' WHERE id = $1")
+            .bind(window)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(enqueue_unique(&pool, "w", "window").await.1);
+    }
+
+    #[sqlx::test]
+    async fn retried_queued_job_takes_its_unique_key_back(pool: Pool<Postgres>) {
+        let (first, _) = enqueue_unique(&pool, "q", "queued").await;
+        sqlx::query("UPDATE jobs SET max_attempts = 2 WHERE id = $1")
+            .bind(first)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(acquire(&pool).await, Some(first));
+
+        let retried = JobRepository::update_job_status(&pool, first, "failed", Some(1), Some("boom")).await.unwrap();
+        assert_eq!(retried.status, "new");
+        assert_eq!(enqueue_unique(&pool, "q", "queued").await, (first, false));
+    }
+
+    #[sqlx::test]
+    async fn retry_is_final_when_another_job_took_the_unique_key(pool: Pool<Postgres>) {
+        let (first, _) = enqueue_unique(&pool, "q", "queued").await;
+        sqlx::query("UPDATE jobs SET max_attempts = 2 WHERE id = $1")
+            .bind(first)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(acquire(&pool).await, Some(first));
+        let (second, created) = enqueue_unique(&pool, "q", "queued").await;
+        assert!(created);
+
+        let failed = JobRepository::update_job_status(&pool, first, "failed", Some(1), Some("boom")).await.unwrap();
+        assert_eq!(failed.status, "failed");
+        assert_eq!(enqueue_unique(&pool, "q", "queued").await, (second, false));
+
+        let error: Option<String> =
+            sqlx::query_scalar("SELECT error FROM job_events WHERE job_id = $1 ORDER BY id DESC LIMIT 1")
+                .bind(first)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            error.as_deref(),
+            Some(format!("boom; not retried because unique key q is held by job {}", second).as_str())
+        );
+    }
+
+    #[sqlx::test]
+    async fn batch_counts_members_as_they_finish(pool: Pool<Postgres>) {
+        let jobs = [
+            job(serde_json::json!({ "name": "a" })),
+            job(serde_json::json!({ "name": "b", "max_attempts": 1 })),
+            job(serde_json::json!({ "name": "c" })),
+        ];
+        let on_failure = serde_json::json!({ "name": "cleanup" });
+        let (batch, ids) =
+            BatchRepository::create(&pool, Uuid::new_v4(), "nightly", None, Some(&on_failure), &jobs, &context("default"))
+                .await
+                .unwrap();
+        let counts = || async { BatchRepository::find_with_counts(&pool, "default", batch.id).await.unwrap().unwrap() };
+
+        let (_, initial) = counts().await;
+        assert_eq!((initial.total, initial.pending, initial.succeeded, initial.failed), (3, 3, 0, 0));
+
+        // Counts each member's attempt, so b's failure is final
+        for _ in 0..3 {
+            acquire(&pool).await.unwrap();
+        }
+        JobRepository::update_job_status(&pool, ids[0].1, "success", None, None).await.unwrap();
+        JobRepository::update_job_status(&pool, ids[1].1, "failed", None, Some("boom")).await.unwrap();
+        let (pending, partial) = counts().await;
+        assert_eq!(pending.status, "pending");
+        assert_eq!((partial.pending, partial.succeeded, partial.failed), (1, 1, 1));
+
+        JobRepository::update_job_status(&pool, ids[2].1, "success", None, None).await.unwrap();
+        let (finished, done) = counts().await;
+        assert_eq!(finished.status, "failed");
+        assert!(finished.follow_up_job_id.is_some());
+        assert_eq!((done.total, done.pending, done.succeeded, done.failed), (3, 0, 2, 1));
+    }
+    #[sqlx::test]
+    async fn acquire_serves_only_the_worker_queues(pool: Pool<Postgres>) {
+        let report = enqueue(&pool, "default", "report").await;
+        let email = enqueue(&pool, "default", "email").await;
+        let acquire_from = |queues: Vec<String>| {
+            let pool = pool.clone();
+            async move {
+                JobRepository::acquire_next_job(&pool, 1, None, &queues).await.unwrap().map(|(job, _)| job.id)
+            }
+        };
+
+        assert_eq!(acquire_from(vec!["email".to_string()]).await, Some(email));
+        assert_eq!(acquire_from(vec!["email".to_string()]).await, None);
+        assert_eq!(acquire_from(Vec::new()).await, Some(report));
+    }
+
+    #[sqlx::test]
+    async fn paused_queue_is_skipped_until_resumed(pool: Pool<Postgres>) {
+        add_tenant(&pool, "acme", None, None).await;
+        let report = enqueue(&pool, "default", "report").await;
+        let email = enqueue(&pool, "default", "email").await;
+        let acme_report = enqueue(&pool, "acme", "report").await;
+
+        QueueRepository::pause(&pool, "default", "report", "admin", None).await.unwrap();
+
+        // Only the default tenant's report queue is paused
+        assert_eq!(acquire(&pool).await, Some(email));
+        assert_eq!(acquire(&pool).await, Some(acme_report));
+        assert_eq!(acquire(&pool).await, None);
+
+        assert!(QueueRepository::resume(&pool, "default", "report").await.unwrap());
+        assert_eq!(acquire(&pool).await, Some(report));
+    }
 }
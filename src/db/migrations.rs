@@ -1,19 +1,131 @@
+use sqlx::migrate::{MigrateError, Migrator};
 use sqlx::{Pool, Postgres, Row};
 use tracing::{error, info, warn};
 
+/// Up migrations embedded at compile time from the migrations/ directory
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Down migrations embedded at compile time, keyed by the version of the up migration they reverse
+///
+/// Each entry must point at a file in down_migrations/ with the same name as its
+/// up migration in migrations/. Pairing is checked by `verify_down_migrations`
+/// before any migration runs, so a missing entry fails fast instead of at rollback time.
+static DOWN_MIGRATIONS: &[(i64, &str)] = &[
+    (
+        20231220000001,
+        include_str!("../../down_migrations/20231220000001_create_jobs_table.sql"),
+    ),
+    (
+        20231220000002,
+        include_str!("../../down_migrations/20231220000002_create_job_events_table.sql"),
+    ),
+    (
+        20231220000003,
+        include_str!("../../down_migrations/20231220000003_add_trace_context_to_jobs.sql"),
+    ),
+    (
+        20231220000004,
+        include_str!("../../down_migrations/20231220000004_add_request_id_to_jobs.sql"),
+    ),
+    (
+        20231220000005,
+        include_str!("../../down_migrations/20231220000005_create_api_keys_table.sql"),
+    ),
+    (
+        20231220000006,
+        include_str!("../../down_migrations/20231220000006_create_tenants_table.sql"),
+    ),
+    (
+        20231220000007,
+        include_str!("../../down_migrations/20231220000007_add_idempotency_key_to_jobs.sql"),
+    ),
+    (
+        20231220000008,
+        include_str!("../../down_migrations/20231220000008_add_unique_key_to_jobs.sql"),
+    ),
+    (
+        20231220000009,
+        include_str!("../../down_migrations/20231220000009_add_payload_to_jobs.sql"),
+    ),
+    (
+        20231220000010,
+        include_str!("../../down_migrations/20231220000010_add_batch_id_to_jobs.sql"),
+    ),
+    (
+        20231220000011,
+        include_str!("../../down_migrations/20231220000011_create_batches_table.sql"),
+    ),
+    (
+        20231220000012,
+        include_str!("../../down_migrations/20231220000012_create_job_dependencies_table.sql"),
+    ),
+    (
+        20231220000013,
+        include_str!("../../down_migrations/20231220000013_create_workflows_table.sql"),
+    ),
+    (
+        20231220000014,
+        include_str!("../../down_migrations/20231220000014_create_webhook_deliveries_table.sql"),
+    ),
+    (
+        20231220000015,
+        include_str!("../../down_migrations/20231220000015_notify_job_events.sql"),
+    ),
+    (
+        20231220000016,
+        include_str!("../../down_migrations/20231220000016_create_job_counts_table.sql"),
+    ),
+    (
+        20231220000017,
+        include_str!("../../down_migrations/20231220000017_create_workers_table.sql"),
+    ),
+    (
+        20231220000018,
+        include_str!("../../down_migrations/20231220000018_create_paused_queues_table.sql"),
+    ),
+];
+
+/// Look up the embedded down migration for a given version
+fn down_migration(version: i64) -> Option<&'static str> {
+    DOWN_MIGRATIONS
+        .iter()
+        .find(|(v, _)| *v == version)
+        .map(|(_, sql)| *sql)
+}
+
+/// Ensure every embedded up migration has exactly one down migration and vice versa
+pub fn verify_down_migrations() -> Result<(), MigrateError> {
+    for migration in MIGRATOR.iter() {
+        if down_migration(migration.version).is_none() {
+            error!(
+                "No down migration registered for {} ({})",
+                migration.version, migration.description
+            );
+            return Err(MigrateError::VersionMissing(migration.version));
+        }
+    }
+
+    for (version, _) in DOWN_MIGRATIONS {
+        if !MIGRATOR.iter().any(|m| m.version == *version) {
+            error!("Down migration {} has no matching up migration", version);
+            return Err(MigrateError::VersionMissing(*version));
+        }
+    }
+
+    Ok(())
+}
+
 /// Run all pending database migrations
 ///
 /// This function embeds the SQL files from the migrations directory
 /// and applies them to the database. It's safe to run multiple times
 /// as sqlx tracks which migrations have already been applied.
-pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::migrate::MigrateError> {
+pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), MigrateError> {
     info!("Running database migrations...");
 
-    // sqlx::migrate!() macro embeds migrations at compile time
-    // from the migrations/ directory
-    sqlx::migrate!("./migrations")
-        .run(pool)
-        .await?;
+    verify_down_migrations()?;
+
+    MIGRATOR.run(pool).await?;
 
     info!("Database migrations completed successfully");
     Ok(())
@@ -21,6 +133,11 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::migrate::
 
 /// Rollback the last N migrations
 ///
+/// All down migrations run in a single transaction together with the removal
+/// of their records from `_sqlx_migrations`, so a failure part-way leaves the
+/// schema untouched. Each down script is executed as a whole (simple query
+/// protocol), which keeps plpgsql function bodies intact.
+///
 /// # Arguments
 /// * `pool` - Database connection pool
 /// * `steps` - Number of migrations to rollback (must be > 0)
@@ -30,101 +147,74 @@ pub async fn run_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::migrate::
 pub async fn rollback_migrations(
     pool: &Pool<Postgres>,
     steps: i64,
-) -> Result<(), sqlx::migrate::MigrateError> {
+) -> Result<(), MigrateError> {
     if steps <= 0 {
         warn!("Invalid rollback steps: {}. Must be greater than 0", steps);
-        return Err(sqlx::migrate::MigrateError::VersionMissing(0));
+        return Err(MigrateError::VersionMissing(0));
     }
 
+    verify_down_migrations()?;
+
     info!("Rolling back {} migration(s)...", steps);
 
-    for i in 0..steps {
-        info!("Rolling back migration {} of {}", i + 1, steps);
+    let mut tx = pool.begin().await.map_err(|e| {
+        error!("Failed to start rollback transaction: {:?}", e);
+        MigrateError::Execute(e)
+    })?;
 
-        // Get the latest applied migration
-        let latest = sqlx::query(
-            "SELECT version, description FROM _sqlx_migrations ORDER BY version DESC LIMIT 1"
-        )
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| {
-            error!("Failed to query migrations table: {:?}", e);
-            sqlx::migrate::MigrateError::Execute(e.into())
+    // Latest applied migrations first
+    let applied = sqlx::query(
+        "SELECT version, description FROM _sqlx_migrations ORDER BY version DESC LIMIT $1"
+    )
+    .bind(steps)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        error!("Failed to query migrations table: {:?}", e);
+        MigrateError::Execute(e)
+    })?;
+
+    if applied.is_empty() {
+        info!("No more migrations to rollback");
+        return Ok(());
+    }
+
+    for (i, row) in applied.iter().enumerate() {
+        let version: i64 = row.try_get("version").map_err(MigrateError::Execute)?;
+        let description: String = row.try_get("description").map_err(MigrateError::Execute)?;
+
+        info!("Rolling back migration {} of {}: {} ({})", i + 1, applied.len(), version, description);
+
+        let down_sql = down_migration(version).ok_or_else(|| {
+            error!("No embedded down migration for version {}", version);
+            MigrateError::VersionMissing(version)
         })?;
 
-        match latest {
-            Some(row) => {
-                let version: i64 = row.try_get("version").map_err(|e| {
-                    error!("Failed to get version: {:?}", e);
-                    sqlx::migrate::MigrateError::Execute(e.into())
-                })?;
-                let description: String = row.try_get("description").map_err(|e| {
-                    error!("Failed to get description: {:?}", e);
-                    sqlx::migrate::MigrateError::Execute(e.into())
-                })?;
-
-                info!("Rolling back migration: {} ({})", version, description);
-
-                // Read the down migration SQL file
-                let down_file = format!("down_migrations/{}_{}.sql", version, description);
-                let down_sql = std::fs::read_to_string(&down_file).map_err(|e| {
-                    error!("Failed to read down migration file '{}': {:?}", down_file, e);
-                    error!("Make sure down migration files exist in down_migrations/ directory");
-
-                    // Return VersionMissing error
-                    sqlx::migrate::MigrateError::VersionMissing(version)
-                })?;
-
-                // Execute the down migration SQL
-                // Split by semicolon and execute each statement separately
-                for statement in down_sql.split(';') {
-                    // Remove comment lines and trim
-                    let cleaned: String = statement
-                        .lines()
-                        .filter(|line| {
-                            let trimmed_line = line.trim();
-                            !trimmed_line.is_empty() && !trimmed_line.starts_with("--")
-                        })
-                        .collect::<Vec<&str>>()
-                        .join("\n");
-
-                    let trimmed = cleaned.trim();
-                    if trimmed.is_empty() {
-                        continue;
-                    }
-
-                    info!("Executing down migration statement: {}", trimmed);
-
-                    sqlx::query(trimmed)
-                        .execute(pool)
-                        .await
-                        .map_err(|e| {
-                            error!("Failed to execute down migration statement: {:?}", e);
-                            error!("Statement: {}", trimmed);
-                            sqlx::migrate::MigrateError::Execute(e.into())
-                        })?;
-                }
-
-                // Remove the migration from the tracking table
-                sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
-                    .bind(version)
-                    .execute(pool)
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to delete migration record: {:?}", e);
-                        sqlx::migrate::MigrateError::Execute(e.into())
-                    })?;
-
-                info!("Successfully rolled back migration: {} ({})", version, description);
-            }
-            None => {
-                info!("No more migrations to rollback");
-                break;
-            }
-        }
+        sqlx::raw_sql(down_sql)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to execute down migration {}: {:?}", version, e);
+                MigrateError::Execute(e)
+            })?;
+
+        // Remove the migration from the tracking table
+        sqlx::query("DELETE FROM _sqlx_migrations WHERE version = $1")
+            .bind(version)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!("Failed to delete migration record: {:?}", e);
+                MigrateError::Execute(e)
+            })?;
     }
 
-    info!("Successfully rolled back {} migration(s)", steps);
+    tx.commit().await.map_err(|e| {
+        error!("Failed to commit rollback transaction: {:?}", e);
+        MigrateError::Execute(e)
+    })?;
+
+    info!("Successfully rolled back {} migration(s)", applied.len());
     Ok(())
 }
 
@@ -132,7 +222,7 @@ pub async fn rollback_migrations(
 ///
 /// This removes all migrations, returning the database to its initial state.
 /// Be careful - this will drop all tables and data managed by migrations!
-pub async fn rollback_all_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::migrate::MigrateError> {
+pub async fn rollback_all_migrations(pool: &Pool<Postgres>) -> Result<(), MigrateError> {
     info!("Rolling back ALL migrations to fresh state...");
     warn!("This will remove all database schema changes from migrations!");
 
@@ -140,10 +230,10 @@ pub async fn rollback_all_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::
     let applied = sqlx::query("SELECT COUNT(*) as count FROM _sqlx_migrations")
         .fetch_one(pool)
         .await
-        .map_err(|e| sqlx::migrate::MigrateError::Execute(e.into()))?;
+        .map_err(MigrateError::Execute)?;
 
     let count: i64 = applied.try_get("count")
-        .map_err(|e| sqlx::migrate::MigrateError::Execute(e.into()))?;
+        .map_err(MigrateError::Execute)?;
 
     if count == 0 {
         info!("No migrations to rollback - database is already in fresh state");
@@ -152,7 +242,7 @@ pub async fn rollback_all_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::
 
     info!("Found {} applied migration(s) to rollback", count);
 
-    // Rollback all migrations one by one
+    // Rollback all migrations in one transaction
     rollback_migrations(pool, count).await?;
 
     info!("Successfully rolled back all migrations - database is now in fresh state");
@@ -162,7 +252,7 @@ pub async fn rollback_all_migrations(pool: &Pool<Postgres>) -> Result<(), sqlx::
 /// Refresh database: rollback all migrations and re-apply them
 ///
 /// This is useful for testing or resetting to a clean state with current schema.
-pub async fn refresh_database(pool: &Pool<Postgres>) -> Result<(), sqlx::migrate::MigrateError> {
+pub async fn refresh_database(pool: &Pool<Postgres>) -> Result<(), MigrateError> {
     info!("Refreshing database (rollback all + re-migrate)...");
 
     // Rollback all migrations
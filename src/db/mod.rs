@@ -2,4 +2,12 @@ pub mod connection;
 pub mod migrations;
 pub mod models;
 pub mod job_repository;
+pub mod api_key_repository;
+pub mod tenant_repository;
+pub mod batch_repository;
+pub mod workflow_repository;
+pub mod webhook_repository;
+pub mod stats_repository;
+pub mod worker_repository;
+pub mod queue_repository;
 pub mod cli;
\ No newline at end of file
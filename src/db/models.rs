@@ -1,13 +1,211 @@
 use chrono::NaiveDateTime;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use uuid::Uuid;
 
 /// Database representation of a job with all fields
 #[derive(Debug, FromRow, Serialize)]
 pub struct JobRow {
     pub id: i32,
+    pub tenant_id: String,
     pub name: String,
     pub status: String,
+    pub attempts: i32,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    #[serde(skip_serializing)]
+    pub trace_context: Option<serde_json::Value>,
+    pub request_id: Option<String>,
+    pub idempotency_key: Option<String>,
+    pub unique_key: Option<String>,
+    pub unique_scope: Option<String>,
+    pub unique_until: Option<NaiveDateTime>,
+    pub payload: Option<serde_json::Value>,
+    pub batch_id: Option<Uuid>,
+    pub on_parent_failure: String,
+    pub workflow_id: Option<Uuid>,
+    pub workflow_step: Option<String>,
+    pub max_attempts: i32,
+    pub retry_backoff_secs: i32,
+    /// Earliest time a retried job may be picked up again
+    pub run_at: Option<NaiveDateTime>,
+    /// Receives a signed POST when the job finishes
+    pub callback_url: Option<String>,
+    /// Set by the job's handler on success
+    pub result: Option<serde_json::Value>,
+}
+
+/// Request-scoped metadata stored with newly created jobs
+#[derive(Debug, Clone)]
+pub struct EnqueueContext {
+    /// Tenant of the authenticated caller; owns the job
+    pub tenant_id: String,
+    /// W3C trace context of the request that enqueued the job
+    pub trace_context: Option<serde_json::Value>,
+    /// X-Request-Id of the API call that created the job
+    pub request_id: Option<String>,
+    /// How long an idempotency key keeps mapping to its original job
+    pub idempotency_retention_hours: i32,
+    /// Bulk upload the job belongs to
+    pub batch_id: Option<Uuid>,
+    /// API key of the caller; its webhook secret signs the job's callback
+    pub api_key_id: Option<i32>,
+}
+
+/// Database representation of a single job status transition
+#[derive(Debug, FromRow, Serialize)]
+pub struct JobEventRow {
+    pub id: i64,
+    pub job_id: i32,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub worker_id: Option<i32>,
+    pub attempt: i32,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A status transition as published on the `job_events` channel and streamed to clients
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct JobEventMessage {
+    pub id: i64,
+    pub job_id: i32,
+    #[serde(skip_serializing)]
+    pub tenant_id: String,
+    pub name: String,
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub worker_id: Option<i32>,
+    pub attempt: i32,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Database representation of an API key (the key itself is never stored)
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct ApiKeyRow {
+    pub id: i32,
+    pub tenant_id: String,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// Database representation of a tenant and its quotas
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct TenantRow {
+    pub id: String,
+    pub max_concurrent_jobs: Option<i32>,
+    pub max_queued_jobs: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Database representation of a named batch of jobs
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct BatchRow {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub status: String,
+    pub on_complete: Option<serde_json::Value>,
+    pub on_failure: Option<serde_json::Value>,
+    pub follow_up_job_id: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Member counts of a batch by outcome
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct BatchCounts {
+    pub total: i64,
+    pub pending: i64,
+    pub succeeded: i64,
+    pub failed: i64,
+}
+
+/// Database representation of a workflow expanded into jobs
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WorkflowRow {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// One step of a workflow and the job it was expanded into
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WorkflowStepRow {
+    pub step: String,
+    pub job_id: i32,
+    pub name: String,
+    pub status: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub run_at: Option<NaiveDateTime>,
+    /// Step ids this step waits for
+    pub depends_on: Vec<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// A completion webhook claimed by the dispatcher, with the secret to sign it
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookDeliveryRow {
+    pub id: i64,
+    pub job_id: i32,
+    pub url: String,
+    pub payload: serde_json::Value,
+    pub attempts: i32,
+    pub webhook_secret: Option<String>,
+}
+
+/// Number of a tenant's jobs with one name and status
+#[derive(Debug, Clone, FromRow)]
+pub struct JobCountRow {
+    pub name: String,
+    pub status: String,
+    pub count: i64,
+}
+
+/// Jobs a tenant finished recently, by outcome, and their average run time
+#[derive(Debug, Clone, FromRow)]
+pub struct RecentJobsRow {
+    pub succeeded_last_minute: i64,
+    pub failed_last_minute: i64,
+    pub cancelled_last_minute: i64,
+    pub succeeded_last_hour: i64,
+    pub failed_last_hour: i64,
+    pub cancelled_last_hour: i64,
+    /// Over jobs that ran in the last hour; `None` if none did
+    pub avg_run_time_ms: Option<f64>,
+}
+
+/// A registered worker with the jobs it is running
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct WorkerRow {
+    pub id: Uuid,
+    pub host: String,
+    pub pid: i32,
+    pub worker_id: i32,
+    /// Job names the worker acquires; empty means every name
+    pub queues: Vec<String>,
+    pub permits_in_use: i32,
+    pub started_at: NaiveDateTime,
+    pub last_heartbeat_at: NaiveDateTime,
+    pub heartbeat_age_secs: f64,
+    /// `[{id, tenant_id, name, attempts, started_at}]` of its `processing` jobs of the caller's tenant
+    pub current_jobs: serde_json::Value,
+}
+
+/// A job name workers are not acquiring
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct PausedQueueRow {
+    pub tenant_id: String,
+    pub name: String,
+    pub reason: Option<String>,
+    pub paused_by: String,
+    pub paused_at: NaiveDateTime,
 }
@@ -0,0 +1,74 @@
+use sqlx::{Pool, Postgres};
+use tracing::{info, instrument};
+use crate::db::models::PausedQueueRow;
+
+/// Repository for pausing and resuming a tenant's queues (job names)
+pub struct QueueRepository;
+
+impl QueueRepository {
+    /// Pause a tenant's queue; `acquire_next_job` skips its jobs from the next acquisition on
+    ///
+    /// Pausing a paused queue keeps the original entry and only updates a given reason.
+    #[instrument(name = "QueueRepository::pause", skip(pool))]
+    pub async fn pause(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        name: &str,
+        paused_by: &str,
+        reason: Option<&str>,
+    ) -> Result<PausedQueueRow, sqlx::Error> {
+        let paused = sqlx::query_as!(
+            PausedQueueRow,
+            r#"
+            INSERT INTO paused_queues (tenant_id, name, reason, paused_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id, name) DO UPDATE SET reason = COALESCE(EXCLUDED.reason, paused_queues.reason)
+            RETURNING tenant_id, name, reason, paused_by, paused_at
+            "#,
+            tenant_id,
+            name,
+            reason,
+            paused_by
+        )
+        .fetch_one(pool)
+        .await?;
+
+        info!(tenant_id, queue = name, paused_by, "Queue paused");
+        Ok(paused)
+    }
+
+    /// Resume a tenant's queue
+    ///
+    /// # Returns
+    /// `false` if the queue was not paused
+    #[instrument(name = "QueueRepository::resume", skip(pool))]
+    pub async fn resume(pool: &Pool<Postgres>, tenant_id: &str, name: &str) -> Result<bool, sqlx::Error> {
+        let resumed = sqlx::query!("DELETE FROM paused_queues WHERE tenant_id = $1 AND name = $2", tenant_id, name)
+            .execute(pool)
+            .await?
+            .rows_affected()
+            > 0;
+
+        if resumed {
+            info!(tenant_id, queue = name, "Queue resumed");
+        }
+        Ok(resumed)
+    }
+
+    /// List a tenant's paused queues, most recently paused first
+    #[instrument(name = "QueueRepository::list_paused", skip(pool))]
+    pub async fn list_paused(pool: &Pool<Postgres>, tenant_id: &str) -> Result<Vec<PausedQueueRow>, sqlx::Error> {
+        sqlx::query_as!(
+            PausedQueueRow,
+            r#"
+            SELECT tenant_id, name, reason, paused_by, paused_at
+            FROM paused_queues
+            WHERE tenant_id = $1
+            ORDER BY paused_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
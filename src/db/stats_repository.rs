@@ -0,0 +1,78 @@
+use sqlx::{Pool, Postgres};
+use tracing::instrument;
+use crate::db::models::{JobCountRow, RecentJobsRow};
+
+/// Repository for queue statistics
+///
+/// Counts come from `job_counts`, which a trigger keeps in step with `jobs`; the
+/// remaining queries only touch index ranges (oldest queued job, last hour of
+/// finished jobs), so none of them grows with the size of the table.
+pub struct StatsRepository;
+
+impl StatsRepository {
+    /// Count a tenant's jobs by name and status
+    #[instrument(name = "StatsRepository::count_jobs", skip(pool))]
+    pub async fn count_jobs(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+    ) -> Result<Vec<JobCountRow>, sqlx::Error> {
+        sqlx::query_as!(
+            JobCountRow,
+            r#"
+            SELECT name, status, SUM(count)::BIGINT AS "count!"
+            FROM job_counts
+            WHERE tenant_id = $1
+            GROUP BY name, status
+            HAVING SUM(count) <> 0
+            ORDER BY name, status
+            "#,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Seconds the tenant's oldest `new` job has been waiting, if any is queued
+    #[instrument(name = "StatsRepository::oldest_pending_age_secs", skip(pool))]
+    pub async fn oldest_pending_age_secs(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+    ) -> Result<Option<f64>, sqlx::Error> {
+        sqlx::query_scalar!(
+            r#"
+            SELECT EXTRACT(EPOCH FROM NOW()::TIMESTAMP - MIN(created_at))::FLOAT8
+            FROM jobs
+            WHERE tenant_id = $1 AND status = 'new'
+            "#,
+            tenant_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Jobs the tenant finished in the last minute and hour, and their average run time
+    #[instrument(name = "StatsRepository::recent_jobs", skip(pool))]
+    pub async fn recent_jobs(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+    ) -> Result<RecentJobsRow, sqlx::Error> {
+        sqlx::query_as!(
+            RecentJobsRow,
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE status = 'success' AND finished_at > NOW() - INTERVAL '1 minute') AS "succeeded_last_minute!",
+                COUNT(*) FILTER (WHERE status = 'failed' AND finished_at > NOW() - INTERVAL '1 minute') AS "failed_last_minute!",
+                COUNT(*) FILTER (WHERE status = 'cancelled' AND finished_at > NOW() - INTERVAL '1 minute') AS "cancelled_last_minute!",
+                COUNT(*) FILTER (WHERE status = 'success') AS "succeeded_last_hour!",
+                COUNT(*) FILTER (WHERE status = 'failed') AS "failed_last_hour!",
+                COUNT(*) FILTER (WHERE status = 'cancelled') AS "cancelled_last_hour!",
+                (AVG(EXTRACT(EPOCH FROM finished_at - started_at)) FILTER (WHERE started_at IS NOT NULL) * 1000)::FLOAT8 AS avg_run_time_ms
+            FROM jobs
+            WHERE tenant_id = $1 AND finished_at > NOW() - INTERVAL '1 hour'
+            "#,
+            tenant_id
+        )
+        .fetch_one(pool)
+        .await
+    }
+}
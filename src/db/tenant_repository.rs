@@ -0,0 +1,54 @@
+use sqlx::{Pool, Postgres};
+use tracing::debug;
+use crate::db::models::TenantRow;
+
+/// Repository for tenant database operations
+pub struct TenantRepository;
+
+impl TenantRepository {
+    /// Create a tenant or update its quotas
+    ///
+    /// `None` quotas mean unlimited.
+    pub async fn upsert(
+        pool: &Pool<Postgres>,
+        id: &str,
+        max_concurrent_jobs: Option<i32>,
+        max_queued_jobs: Option<i32>,
+    ) -> Result<TenantRow, sqlx::Error> {
+        debug!(
+            "Upserting tenant {}: max_concurrent_jobs={:?}, max_queued_jobs={:?}",
+            id, max_concurrent_jobs, max_queued_jobs
+        );
+
+        sqlx::query_as!(
+            TenantRow,
+            r#"
+            INSERT INTO tenants (id, max_concurrent_jobs, max_queued_jobs)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO UPDATE
+            SET max_concurrent_jobs = EXCLUDED.max_concurrent_jobs,
+                max_queued_jobs = EXCLUDED.max_queued_jobs
+            RETURNING id, max_concurrent_jobs, max_queued_jobs, created_at
+            "#,
+            id,
+            max_concurrent_jobs,
+            max_queued_jobs
+        )
+        .fetch_one(pool)
+        .await
+    }
+
+    /// List all tenants
+    pub async fn list(pool: &Pool<Postgres>) -> Result<Vec<TenantRow>, sqlx::Error> {
+        sqlx::query_as!(
+            TenantRow,
+            r#"
+            SELECT id, max_concurrent_jobs, max_queued_jobs, created_at
+            FROM tenants
+            ORDER BY id ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,144 @@
+use sqlx::{PgConnection, Pool, Postgres};
+use tracing::{debug, instrument};
+use crate::db::models::WebhookDeliveryRow;
+
+/// Repository for completion webhook deliveries
+pub struct WebhookRepository;
+
+impl WebhookRepository {
+    /// Queue a delivery for every finished job in `job_ids` that has a `callback_url`
+    ///
+    /// Called in the transaction that moves the jobs to a terminal status, so a
+    /// callback is queued exactly when the outcome commits. The payload is built from
+    /// the stored job: id, name, status, result, the last error, attempts and timing.
+    pub async fn enqueue_for_jobs(conn: &mut PgConnection, job_ids: &[i32]) -> Result<u64, sqlx::Error> {
+        let queued = sqlx::query!(
+            r#"
+            INSERT INTO webhook_deliveries (job_id, url, payload)
+            SELECT j.id, j.callback_url, jsonb_build_object(
+                'id', j.id,
+                'name', j.name,
+                'status', j.status,
+                'result', j.result,
+                'error', last_event.error,
+                'attempts', j.attempts,
+                'created_at', j.created_at,
+                'started_at', j.started_at,
+                'finished_at', j.finished_at,
+                'duration_ms', (EXTRACT(EPOCH FROM (j.finished_at - j.started_at)) * 1000)::BIGINT
+            )
+            FROM jobs j
+            CROSS JOIN LATERAL (
+                SELECT error
+                FROM job_events
+                WHERE job_id = j.id
+                ORDER BY id DESC
+                LIMIT 1
+            ) last_event
+            WHERE j.id = ANY($1) AND j.callback_url IS NOT NULL
+            "#,
+            job_ids
+        )
+        .execute(conn)
+        .await?
+        .rows_affected();
+
+        if queued > 0 {
+            debug!("Queued {} completion webhooks", queued);
+        }
+        Ok(queued)
+    }
+
+    /// Claim up to `limit` due deliveries and count the attempt
+    ///
+    /// Claimed deliveries are leased by pushing `next_attempt_at` out by `lease_secs`,
+    /// so another dispatcher skips them while the request is in flight and a crashed
+    /// dispatcher's deliveries are picked up again once the lease ends.
+    #[instrument(name = "WebhookRepository::claim_due", skip(pool))]
+    pub async fn claim_due(
+        pool: &Pool<Postgres>,
+        limit: i64,
+        lease_secs: f64,
+    ) -> Result<Vec<WebhookDeliveryRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WebhookDeliveryRow,
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM webhook_deliveries
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE webhook_deliveries d
+            SET attempts = d.attempts + 1,
+                next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM due
+            WHERE d.id = due.id
+            RETURNING d.id, d.job_id, d.url, d.payload, d.attempts,
+                      (
+                          SELECT k.webhook_secret
+                          FROM jobs j
+                          JOIN api_keys k ON k.id = j.api_key_id
+                          WHERE j.id = d.job_id
+                      ) AS webhook_secret
+            "#,
+            limit,
+            lease_secs
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record a successful delivery
+    pub async fn mark_delivered(pool: &Pool<Postgres>, id: i64, status_code: i32) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'delivered', delivered_at = NOW(), last_status_code = $2, last_error = NULL
+            WHERE id = $1
+            "#,
+            id,
+            status_code
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a failed attempt and schedule the next one, or give up after `max_attempts`
+    ///
+    /// # Returns
+    /// `true` if the delivery will be retried
+    pub async fn mark_failed(
+        pool: &Pool<Postgres>,
+        id: i64,
+        status_code: Option<i32>,
+        error: &str,
+        max_attempts: i32,
+        backoff_secs: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let status = sqlx::query_scalar!(
+            r#"
+            UPDATE webhook_deliveries
+            SET last_status_code = $2,
+                last_error = $3,
+                status = CASE WHEN attempts >= $4 THEN 'failed' ELSE 'pending' END,
+                next_attempt_at = NOW() + make_interval(secs => LEAST($5 * POWER(2, attempts - 1), 3600))
+            WHERE id = $1
+            RETURNING status
+            "#,
+            id,
+            status_code,
+            error,
+            max_attempts,
+            backoff_secs as f64
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(status == "pending")
+    }
+}
@@ -0,0 +1,127 @@
+use sqlx::{Pool, Postgres};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+use crate::db::models::WorkerRow;
+
+/// Repository for the worker registry
+pub struct WorkerRepository;
+
+impl WorkerRepository {
+    /// Add a worker entry; `id` is also stored as `locked_by` on the jobs it acquires
+    #[instrument(name = "WorkerRepository::register", skip(pool))]
+    pub async fn register(
+        pool: &Pool<Postgres>,
+        id: Uuid,
+        host: &str,
+        pid: i32,
+        worker_id: i32,
+        queues: &[String],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            INSERT INTO workers (id, host, pid, worker_id, queues)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            host,
+            pid,
+            worker_id,
+            queues
+        )
+        .execute(pool)
+        .await?;
+
+        debug!("Registered worker {} ({}:{} #{})", id, host, pid, worker_id);
+        Ok(())
+    }
+
+    /// Refresh a worker's heartbeat and permit count
+    ///
+    /// # Returns
+    /// `false` if the entry is gone, e.g. removed as stale during a long pause
+    #[instrument(name = "WorkerRepository::heartbeat", skip(pool))]
+    pub async fn heartbeat(
+        pool: &Pool<Postgres>,
+        id: Uuid,
+        permits_in_use: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE workers
+            SET last_heartbeat_at = NOW(), permits_in_use = $2
+            WHERE id = $1
+            "#,
+            id,
+            permits_in_use
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        Ok(updated > 0)
+    }
+
+    /// Remove a worker entry on shutdown
+    #[instrument(name = "WorkerRepository::deregister", skip(pool))]
+    pub async fn deregister(pool: &Pool<Postgres>, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!("DELETE FROM workers WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove workers whose last heartbeat is older than `stale_secs`
+    ///
+    /// Their `processing` jobs are left as they are.
+    #[instrument(name = "WorkerRepository::remove_stale", skip(pool))]
+    pub async fn remove_stale(pool: &Pool<Postgres>, stale_secs: i64) -> Result<u64, sqlx::Error> {
+        let removed = sqlx::query!(
+            r#"
+            DELETE FROM workers
+            WHERE last_heartbeat_at < NOW() - make_interval(secs => $1)
+            "#,
+            stale_secs as f64
+        )
+        .execute(pool)
+        .await?
+        .rows_affected();
+
+        if removed > 0 {
+            debug!("Removed {} stale workers", removed);
+        }
+        Ok(removed)
+    }
+
+    /// List every registered worker with the jobs of `tenant_id` it is running
+    #[instrument(name = "WorkerRepository::list", skip(pool))]
+    pub async fn list(pool: &Pool<Postgres>, tenant_id: &str) -> Result<Vec<WorkerRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkerRow,
+            r#"
+            SELECT
+                w.id, w.host, w.pid, w.worker_id, w.queues, w.permits_in_use, w.started_at, w.last_heartbeat_at,
+                EXTRACT(EPOCH FROM NOW()::TIMESTAMP - w.last_heartbeat_at)::FLOAT8 AS "heartbeat_age_secs!",
+                COALESCE(
+                    (
+                        SELECT jsonb_agg(jsonb_build_object(
+                            'id', j.id,
+                            'tenant_id', j.tenant_id,
+                            'name', j.name,
+                            'attempts', j.attempts,
+                            'started_at', j.started_at
+                        ) ORDER BY j.id)
+                        FROM jobs j
+                        WHERE j.locked_by = w.id AND j.status = 'processing' AND j.tenant_id = $1
+                    ),
+                    '[]'::jsonb
+                ) AS "current_jobs!"
+            FROM workers w
+            ORDER BY w.host, w.pid, w.worker_id
+            "#,
+            tenant_id
+        )
+        .fetch_all(pool)
+        .await
+    }
+}
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use sqlx::{PgConnection, Pool, Postgres};
+use tracing::{debug, info, instrument};
+use uuid::Uuid;
+use crate::api::workflow::CreateWorkflow;
+use crate::db::job_repository::{InsertError, JobRepository};
+use crate::db::models::{EnqueueContext, WorkflowRow, WorkflowStepRow};
+use crate::db::webhook_repository::WebhookRepository;
+
+/// Repository for workflow database operations
+pub struct WorkflowRepository;
+
+impl WorkflowRepository {
+    /// Create a workflow and expand its steps into jobs in one transaction
+    ///
+    /// Steps are inserted in `order` (parents first) so each step's dependencies can
+    /// be attached by job id. Steps with parents start `blocked`. The whole workflow
+    /// counts against the tenant's queued-job quota.
+    ///
+    /// # Returns
+    /// The workflow and `(index into steps, job id)` for every step, in `order`
+    #[instrument(name = "WorkflowRepository::create", skip_all, fields(tenant_id = %context.tenant_id, %workflow_id, steps = workflow.steps.len()))]
+    pub async fn create(
+        pool: &Pool<Postgres>,
+        workflow_id: Uuid,
+        workflow: &CreateWorkflow,
+        order: &[usize],
+        context: &EnqueueContext,
+    ) -> Result<(WorkflowRow, Vec<(usize, i32)>), InsertError> {
+        debug!("Creating workflow {} ({}) with {} steps", workflow_id, workflow.name, workflow.steps.len());
+
+        let mut tx = pool.begin().await?;
+
+        let row = sqlx::query_as!(
+            WorkflowRow,
+            r#"
+            INSERT INTO workflows (id, tenant_id, name)
+            VALUES ($1, $2, $3)
+            RETURNING id, tenant_id, name, status, created_at, finished_at
+            "#,
+            workflow_id,
+            context.tenant_id,
+            workflow.name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        JobRepository::check_queue_quota(&mut tx, &context.tenant_id, workflow.steps.len() as i64).await?;
+
+        let mut job_ids: HashMap<&str, i32> = HashMap::with_capacity(order.len());
+        let mut ids = Vec::with_capacity(order.len());
+        for &index in order {
+            let step = &workflow.steps[index];
+            let (max_attempts, backoff_secs) = step
+                .retry
+                .map_or((1, 0), |retry| (retry.max_attempts as i32, retry.backoff_secs as i32));
+
+            let job_id = sqlx::query_scalar!(
+                r#"
+                INSERT INTO jobs (
+                    tenant_id, name, status, trace_context, request_id, payload, on_parent_failure,
+                    workflow_id, workflow_step, max_attempts, retry_backoff_secs
+                )
+                VALUES ($1, $2, 'new', $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING id
+                "#,
+                context.tenant_id,
+                step.name,
+                context.trace_context,
+                context.request_id,
+                step.payload,
+                step.on_parent_failure.as_str(),
+                workflow_id,
+                step.id,
+                max_attempts,
+                backoff_secs
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+
+            JobRepository::record_event(&mut tx, job_id, None, "new", None, 0, None).await?;
+
+            // Parents precede their children in `order`, so they all have ids by now
+            let parents: Vec<i32> = workflow
+                .parents_of(&step.id)
+                .filter_map(|parent| job_ids.get(parent).copied())
+                .collect();
+            if !parents.is_empty() {
+                JobRepository::attach_dependencies(&mut tx, &context.tenant_id, job_id, &parents, step.on_parent_failure)
+                    .await?;
+            }
+
+            job_ids.insert(step.id.as_str(), job_id);
+            ids.push((index, job_id));
+        }
+
+        tx.commit().await?;
+
+        debug!("Workflow {} created with {} jobs", workflow_id, ids.len());
+        Ok((row, ids))
+    }
+
+    /// Find a tenant's workflow together with its steps, in insertion order
+    #[instrument(name = "WorkflowRepository::find_with_steps", skip(pool))]
+    pub async fn find_with_steps(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        workflow_id: Uuid,
+    ) -> Result<Option<(WorkflowRow, Vec<WorkflowStepRow>)>, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+
+        let Some(workflow) = Self::find_in(&mut conn, tenant_id, workflow_id).await? else {
+            return Ok(None);
+        };
+
+        let steps = sqlx::query_as!(
+            WorkflowStepRow,
+            r#"
+            SELECT
+                j.workflow_step AS "step!",
+                j.id AS job_id,
+                j.name,
+                j.status,
+                j.attempts,
+                j.max_attempts,
+                j.run_at,
+                ARRAY(
+                    SELECT p.workflow_step
+                    FROM job_dependencies d
+                    JOIN jobs p ON p.id = d.depends_on_id
+                    WHERE d.job_id = j.id
+                    ORDER BY p.id
+                ) AS "depends_on!: Vec<String>",
+                j.updated_at
+            FROM jobs j
+            WHERE j.workflow_id = $1
+            ORDER BY j.id
+            "#,
+            workflow_id
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        Ok(Some((workflow, steps)))
+    }
+
+    /// Cancel every step of a running workflow that has not started yet
+    ///
+    /// The workflow is finished as `cancelled` right away. Steps already processing
+    /// run to completion but are not retried, and release no children since those
+    /// were cancelled here.
+    ///
+    /// # Returns
+    /// - `Ok(Some((workflow, cancelled)))` - The cancelled workflow and the number of steps cancelled
+    /// - `Ok(None)` - No such running workflow for this tenant
+    #[instrument(name = "WorkflowRepository::cancel", skip(pool))]
+    pub async fn cancel(
+        pool: &Pool<Postgres>,
+        tenant_id: &str,
+        workflow_id: Uuid,
+    ) -> Result<Option<(WorkflowRow, u64)>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let running = sqlx::query_scalar!(
+            r#"
+            SELECT id
+            FROM workflows
+            WHERE id = $1 AND tenant_id = $2 AND finished_at IS NULL
+            FOR UPDATE
+            "#,
+            workflow_id,
+            tenant_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if running.is_none() {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        // Locking the pending steps waits out a concurrent acquire; a step it
+        // moved to 'processing' no longer matches and is left running
+        let cancelled = sqlx::query_scalar!(
+            r#"
+            WITH pending AS (
+                SELECT id, status
+                FROM jobs
+                WHERE workflow_id = $1 AND status IN ('new', 'blocked')
+                FOR UPDATE
+            ),
+            cancelled AS (
+                UPDATE jobs j
+                SET status = 'cancelled'
+                FROM pending
+                WHERE j.id = pending.id
+                RETURNING j.id, pending.status AS from_status, j.attempts
+            ),
+            events AS (
+                INSERT INTO job_events (job_id, from_status, to_status, attempt, error)
+                SELECT id, from_status, 'cancelled', attempts, 'Workflow cancelled'
+                FROM cancelled
+            )
+            SELECT id AS "id!" FROM cancelled
+            "#,
+            workflow_id
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        WebhookRepository::enqueue_for_jobs(&mut tx, &cancelled).await?;
+        let cancelled = cancelled.len() as u64;
+
+        let workflow = sqlx::query_as!(
+            WorkflowRow,
+            r#"
+            UPDATE workflows
+            SET status = 'cancelled', finished_at = NOW()
+            WHERE id = $1
+            RETURNING id, tenant_id, name, status, created_at, finished_at
+            "#,
+            workflow_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        info!(workflow_id = %workflow_id, cancelled, "Workflow cancelled");
+        Ok(Some((workflow, cancelled)))
+    }
+
+    /// Finish the workflow if none of its steps are pending any more
+    ///
+    /// Called in the transaction that moves a step to a terminal status. Like
+    /// batches, the workflow row is locked before counting so exactly one of two
+    /// concurrently finishing steps settles it. A workflow with any failed or
+    /// cancelled step becomes `failed`, otherwise `succeeded`.
+    pub async fn on_step_finished(
+        conn: &mut PgConnection,
+        workflow_id: Uuid,
+    ) -> Result<(), sqlx::Error> {
+        let running = sqlx::query_scalar!(
+            "SELECT id FROM workflows WHERE id = $1 AND finished_at IS NULL FOR UPDATE",
+            workflow_id
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        // Already finished or cancelled
+        if running.is_none() {
+            return Ok(());
+        }
+
+        let counts = sqlx::query!(
+            r#"
+            SELECT
+                COUNT(*) FILTER (WHERE status IN ('new', 'processing', 'blocked')) AS "pending!",
+                COUNT(*) FILTER (WHERE status IN ('failed', 'cancelled')) AS "failed!"
+            FROM jobs
+            WHERE workflow_id = $1
+            "#,
+            workflow_id
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if counts.pending > 0 {
+            return Ok(());
+        }
+
+        let status = if counts.failed > 0 { "failed" } else { "succeeded" };
+
+        sqlx::query!(
+            r#"
+            UPDATE workflows
+            SET status = $2, finished_at = NOW()
+            WHERE id = $1
+            "#,
+            workflow_id,
+            status
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        info!(workflow_id = %workflow_id, status, "Workflow finished");
+        Ok(())
+    }
+
+    async fn find_in(
+        conn: &mut PgConnection,
+        tenant_id: &str,
+        workflow_id: Uuid,
+    ) -> Result<Option<WorkflowRow>, sqlx::Error> {
+        sqlx::query_as!(
+            WorkflowRow,
+            r#"
+            SELECT id, tenant_id, name, status, created_at, finished_at
+            FROM workflows
+            WHERE id = $1 AND tenant_id = $2
+            "#,
+            workflow_id,
+            tenant_id
+        )
+        .fetch_optional(conn)
+        .await
+    }
+}
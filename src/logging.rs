@@ -0,0 +1,78 @@
+use opentelemetry_sdk::trace::Tracer;
+use tracing_appender::rolling::RollingFileAppender;
+use tracing_subscriber::{
+    filter::LevelFilter, fmt, layer::Layered, layer::SubscriberExt, util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+use crate::config::LogFormat;
+
+/// A type-erased layer so pretty and JSON layers can share one subscriber
+type BoxedLayer = Box<dyn Layer<Layered<EnvFilter, Registry>> + Send + Sync>;
+
+/// Initialize file-based logging with daily rotation and level separation
+///
+/// Log files will be created as: logs/info.log.2024-12-22, logs/error.log.2024-12-22, etc.
+/// With `LogFormat::Json` every layer (console included) writes one JSON object per line,
+/// with event fields flattened to the top level and the current span's fields
+/// (`job_id`, `worker_id`, `job_name`, ...) attached under `span`.
+///
+/// When a `tracer` is given, spans are also exported through OpenTelemetry.
+pub fn init(log_dir: &str, format: LogFormat, tracer: Option<Tracer>) {
+    let env_filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| "info".into());
+
+    // Create daily rotating file appenders for each log level
+    let info_file = tracing_appender::rolling::daily(log_dir, "info.log");
+    let warn_file = tracing_appender::rolling::daily(log_dir, "warn.log");
+    let error_file = tracing_appender::rolling::daily(log_dir, "error.log");
+    let debug_file = tracing_appender::rolling::daily(log_dir, "debug.log");
+
+    let mut layers = vec![
+        console_layer(format),
+        file_layer(info_file, LevelFilter::INFO, format),
+        file_layer(warn_file, LevelFilter::WARN, format),
+        file_layer(error_file, LevelFilter::ERROR, format),
+        file_layer(debug_file, LevelFilter::DEBUG, format),
+    ];
+
+    if let Some(tracer) = tracer {
+        layers.push(tracing_opentelemetry::layer().with_tracer(tracer).boxed());
+    }
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(layers)
+        .init();
+}
+
+/// Console/stdout layer for terminal output (colored when pretty)
+fn console_layer(format: LogFormat) -> BoxedLayer {
+    let layer = fmt::layer().with_writer(std::io::stdout);
+
+    match format {
+        LogFormat::Pretty => layer.with_ansi(true).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    }
+}
+
+/// File layer that only accepts events at `level` or above
+fn file_layer(writer: RollingFileAppender, level: LevelFilter, format: LogFormat) -> BoxedLayer {
+    let layer = fmt::layer().with_writer(writer).with_ansi(false);
+
+    match format {
+        LogFormat::Pretty => layer.with_filter(level).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_filter(level)
+            .boxed(),
+    }
+}
@@ -1,28 +1,43 @@
-use actix_web::{App, HttpResponse, HttpServer, Responder, guard, web};
+use actix_web::{App, HttpResponse, HttpServer, Responder, guard, middleware::from_fn, web};
 use actix_multipart::form::MultipartFormConfig;
 use std::sync::Arc;
 use tokio::sync::Semaphore;
 use tracing::info;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer, filter::LevelFilter};
+use tracing_actix_web::TracingLogger;
 mod api;
 use crate::api::{
     dummy::dummy_config,
-    job::{handlers::job_config, JobService},
+    job::{handlers::job_config, stream::JobEventBroadcaster, JobService},
+    batch::{handlers::batch_config, BatchService},
+    workflow::{handlers::workflow_config, WorkflowService},
     state::{AppState, state_config},
     validation,
     health::health_config,
+    metrics::metrics_config,
+    stats::{handlers::stats_config, StatsService},
+    workers::{handlers::workers_config, WorkerService},
+    queues::{handlers::queues_config, QueueService},
+    request_id::request_id_middleware,
+    auth::require_jobs_read,
 };
 mod config;
 mod db;
+mod logging;
+mod metrics;
+mod telemetry;
 mod worker;
 mod shutdown;
-use crate::worker::JobWorker;
+use crate::worker::{FanOut, HandlerRegistry, JobWorker, WebhookDispatcher};
 use crate::shutdown::ShutdownCoordinator;
 
 
 
 fn config(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("/test").route(web::route().to(test)));
+    cfg.service(
+        web::resource("/test")
+            .wrap(from_fn(require_jobs_read))
+            .route(web::route().to(test)),
+    );
 }
 
 async fn test() -> impl Responder {
@@ -40,6 +55,16 @@ async fn main() -> std::io::Result<()> {
         max_concurrent_jobs,
         num_workers,
         log_dir,
+        log_format,
+        idempotency_retention_hours,
+        otel_endpoint,
+        webhook_max_attempts,
+        webhook_backoff_secs,
+        webhook_timeout_secs,
+        webhook_deny_cidrs,
+        worker_heartbeat_secs,
+        worker_stale_secs,
+        worker_queues,
     } = config::Config::from_env()
         .expect("Failed to load configuration");
 
@@ -47,52 +72,13 @@ async fn main() -> std::io::Result<()> {
     std::fs::create_dir_all(&log_dir)
         .expect("Failed to create logs directory");
 
-    // Initialize file-based logging with daily rotation and level separation
-    // Log files will be created as: logs/info.2024-12-22.log, logs/error.2024-12-22.log, etc.
-    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
-        .unwrap_or_else(|_| "info".into());
-
-    // Create daily rotating file appenders for each log level
-    let info_file = tracing_appender::rolling::daily(&log_dir, "info.log");
-    let warn_file = tracing_appender::rolling::daily(&log_dir, "warn.log");
-    let error_file = tracing_appender::rolling::daily(&log_dir, "error.log");
-    let debug_file = tracing_appender::rolling::daily(&log_dir, "debug.log");
-
-    // Create layers for each log level
-    let info_layer = tracing_subscriber::fmt::layer()
-        .with_writer(info_file)
-        .with_ansi(false)
-        .with_filter(LevelFilter::INFO);
-
-    let warn_layer = tracing_subscriber::fmt::layer()
-        .with_writer(warn_file)
-        .with_ansi(false)
-        .with_filter(LevelFilter::WARN);
-
-    let error_layer = tracing_subscriber::fmt::layer()
-        .with_writer(error_file)
-        .with_ansi(false)
-        .with_filter(LevelFilter::ERROR);
-
-    let debug_layer = tracing_subscriber::fmt::layer()
-        .with_writer(debug_file)
-        .with_ansi(false)
-        .with_filter(LevelFilter::DEBUG);
-
-    // Create console/stdout layer for terminal output
-    let console_layer = tracing_subscriber::fmt::layer()
-        .with_writer(std::io::stdout)
-        .with_ansi(true);
-
-    // Initialize the subscriber with all layers (including console)
-    tracing_subscriber::registry()
-        .with(env_filter)
-        .with(console_layer) // Add console output
-        .with(info_layer)
-        .with(warn_layer)
-        .with(error_layer)
-        .with(debug_layer)
-        .init();
+    // Initialize OTLP trace export if a collector is configured
+    let tracer = otel_endpoint.as_deref().map(|endpoint| {
+        telemetry::init_tracer(endpoint).expect("Failed to initialize OpenTelemetry tracing")
+    });
+
+    // Initialize console + per-level file logging in the configured format
+    logging::init(&log_dir, log_format, tracer);
 
     // Get database connection pool
     let pool = db::connection::get_connection(&database_url, max_db_connections).await
@@ -123,13 +109,19 @@ async fn main() -> std::io::Result<()> {
     let semaphore = Arc::new(Semaphore::new(max_concurrent_jobs));
     let mut worker_handles = Vec::new();
 
+    // Jobs whose name has no handler fall back to simulated processing
+    let handlers = Arc::new(HandlerRegistry::default().register("fan_out", FanOut));
+
     for worker_id in 1..=num_workers {
         let worker_pool = pool.clone();
+        let worker_handlers = handlers.clone();
         let worker_semaphore = semaphore.clone();
         let worker_shutdown_rx = shutdown_rx.clone();
+        let worker_queues = worker_queues.clone();
 
         let handle = tokio::spawn(async move {
-            let job_worker = JobWorker::new(worker_pool);
+            let job_worker =
+                JobWorker::new(worker_pool, worker_handlers, worker_queues, worker_heartbeat_secs, worker_stale_secs);
             job_worker.run(worker_id, worker_semaphore, worker_shutdown_rx).await;
         });
 
@@ -137,14 +129,39 @@ async fn main() -> std::io::Result<()> {
         info!("Spawned worker {}", worker_id);
     }
 
+    // Deliver completion webhooks in the background; stops with the workers
+    let dispatcher = WebhookDispatcher::new(
+        pool.clone(),
+        webhook_max_attempts,
+        webhook_backoff_secs,
+        webhook_timeout_secs,
+        webhook_deny_cidrs,
+    );
+    let dispatcher_shutdown_rx = shutdown_rx.clone();
+    worker_handles.push(tokio::spawn(async move {
+        dispatcher.run(dispatcher_shutdown_rx).await;
+    }));
+    info!("Spawned webhook dispatcher");
+
     // Clone pool for HTTP server (original will be used for shutdown)
     let server_pool = pool.clone();
 
+    // Semaphore is shared with the metrics endpoint to report available permits
+    let server_semaphore = semaphore.clone();
+
+    // One LISTEN connection feeds the SSE streams of every HTTP worker
+    let job_events = web::Data::new(JobEventBroadcaster::start(pool.clone()));
+
     let server = HttpServer::new(move || {
         let my_state = web::Data::new(AppState::new("my_app"));
 
         // Create JobService with database pool
-        let job_service = web::Data::new(JobService::new(server_pool.clone()));
+        let job_service = web::Data::new(JobService::new(server_pool.clone(), idempotency_retention_hours));
+        let batch_service = web::Data::new(BatchService::new(server_pool.clone(), idempotency_retention_hours));
+        let workflow_service = web::Data::new(WorkflowService::new(server_pool.clone(), idempotency_retention_hours));
+        let stats_service = web::Data::new(StatsService::new(server_pool.clone()));
+        let worker_service = web::Data::new(WorkerService::new(server_pool.clone()));
+        let queue_service = web::Data::new(QueueService::new(server_pool.clone()));
 
         // Configure payload size limits globally
         let payload_config = web::PayloadConfig::default()
@@ -154,28 +171,49 @@ async fn main() -> std::io::Result<()> {
             .total_limit(max_payload_size);
 
         App::new()
+            .wrap(from_fn(request_id_middleware)) // Accept/generate X-Request-Id
+            .wrap(TracingLogger::default()) // Request span, continues incoming traceparent
             .app_data(web::Data::new(server_pool.clone())) // Share DB pool across workers
             .app_data(job_service) // Inject JobService
+            .app_data(batch_service) // Inject BatchService
+            .app_data(workflow_service) // Inject WorkflowService
+            .app_data(stats_service) // Inject StatsService
+            .app_data(worker_service) // Inject WorkerService
+            .app_data(queue_service) // Inject QueueService
+            .app_data(job_events.clone()) // Live job status transitions for SSE
+            .app_data(web::Data::from(server_semaphore.clone())) // Worker semaphore for metrics
             .app_data(my_state)
             .app_data(payload_config) // Global payload size limit
             .app_data(multipart_config) // Global multipart/file upload size limit
             .app_data(validation::json_config()) // Global validation config
             .configure(health_config) // Health check endpoints
+            .configure(metrics_config) // Prometheus metrics endpoint
+            .configure(stats_config) // Queue statistics
+            .configure(workers_config) // Worker registry
+            .configure(queues_config) // Pause/resume queues
             .configure(config)
             .configure(state_config)
             .configure(dummy_config)
             .configure(job_config)
+            .configure(batch_config)
+            .configure(workflow_config)
             .service(
                 web::scope("/guard")
                     .guard(guard::Host("www.tajul.com"))
+                    .wrap(from_fn(require_jobs_read))
                     .route("", web::to(|| async { HttpResponse::Ok().body("tajul") })),
             )
             .service(
                 web::scope("/guard")
                     .guard(guard::Host("www.saajan.com"))
+                    .wrap(from_fn(require_jobs_read))
                     .route("", web::to(|| async { HttpResponse::Ok().body("saajan") })),
             )
-            .route("/guard", web::to(HttpResponse::Ok))
+            .service(
+                web::resource("/guard")
+                    .wrap(from_fn(require_jobs_read))
+                    .to(HttpResponse::Ok),
+            )
     });
 
     info!("Server starting on http://127.0.0.1:8080");
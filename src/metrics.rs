@@ -0,0 +1,148 @@
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::sync::OnceLock;
+
+/// Process-wide Prometheus metrics
+///
+/// Counters and histograms are updated where the event happens (service, worker).
+/// Gauges that describe current state (semaphore, pool, queue depth) are refreshed
+/// by the `/metrics` handler at scrape time.
+pub struct Metrics {
+    registry: Registry,
+    pub jobs_created: IntCounterVec,
+    pub jobs_acquired: IntCounterVec,
+    pub jobs_succeeded: IntCounterVec,
+    pub jobs_failed: IntCounterVec,
+    pub jobs_retried: IntCounterVec,
+    pub queue_wait_seconds: HistogramVec,
+    pub run_duration_seconds: HistogramVec,
+    pub semaphore_permits_available: IntGauge,
+    pub db_pool_size: IntGauge,
+    pub db_pool_idle: IntGauge,
+    pub queue_depth: IntGaugeVec,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Get the global metrics instance, registering all collectors on first use
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let jobs_created = IntCounterVec::new(
+            Opts::new("jobs_created_total", "Jobs created via the API"),
+            &["name"],
+        )
+        .expect("valid jobs_created_total metric");
+
+        let jobs_acquired = IntCounterVec::new(
+            Opts::new("jobs_acquired_total", "Jobs acquired by workers"),
+            &["name"],
+        )
+        .expect("valid jobs_acquired_total metric");
+
+        let jobs_succeeded = IntCounterVec::new(
+            Opts::new("jobs_succeeded_total", "Jobs that completed successfully"),
+            &["name"],
+        )
+        .expect("valid jobs_succeeded_total metric");
+
+        let jobs_failed = IntCounterVec::new(
+            Opts::new("jobs_failed_total", "Jobs that failed their last attempt"),
+            &["name"],
+        )
+        .expect("valid jobs_failed_total metric");
+
+        let jobs_retried = IntCounterVec::new(
+            Opts::new("jobs_retried_total", "Failed attempts put back to 'new' for a retry"),
+            &["name"],
+        )
+        .expect("valid jobs_retried_total metric");
+
+        // Queue wait: 0.1s .. ~14min
+        let queue_wait_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "job_queue_wait_seconds",
+                "Time between a job becoming runnable and its acquisition by a worker",
+            )
+            .buckets(exponential_buckets(0.1, 2.0, 14).expect("valid buckets")),
+            &["name"],
+        )
+        .expect("valid job_queue_wait_seconds metric");
+
+        // Run duration: 50ms .. ~100s
+        let run_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "job_run_duration_seconds",
+                "Time spent processing a job after acquiring a semaphore permit",
+            )
+            .buckets(exponential_buckets(0.05, 2.0, 12).expect("valid buckets")),
+            &["name"],
+        )
+        .expect("valid job_run_duration_seconds metric");
+
+        let semaphore_permits_available = IntGauge::new(
+            "semaphore_permits_available",
+            "Semaphore permits currently available for job processing",
+        )
+        .expect("valid semaphore_permits_available metric");
+
+        let db_pool_size = IntGauge::new(
+            "db_pool_size",
+            "Connections currently open in the sqlx pool",
+        )
+        .expect("valid db_pool_size metric");
+
+        let db_pool_idle = IntGauge::new(
+            "db_pool_idle",
+            "Idle connections in the sqlx pool",
+        )
+        .expect("valid db_pool_idle metric");
+
+        let queue_depth = IntGaugeVec::new(
+            Opts::new("jobs_queue_depth", "Number of jobs by status"),
+            &["status"],
+        )
+        .expect("valid jobs_queue_depth metric");
+
+        registry.register(Box::new(jobs_created.clone())).expect("register jobs_created_total");
+        registry.register(Box::new(jobs_acquired.clone())).expect("register jobs_acquired_total");
+        registry.register(Box::new(jobs_succeeded.clone())).expect("register jobs_succeeded_total");
+        registry.register(Box::new(jobs_failed.clone())).expect("register jobs_failed_total");
+        registry.register(Box::new(jobs_retried.clone())).expect("register jobs_retried_total");
+        registry.register(Box::new(queue_wait_seconds.clone())).expect("register job_queue_wait_seconds");
+        registry.register(Box::new(run_duration_seconds.clone())).expect("register job_run_duration_seconds");
+        registry.register(Box::new(semaphore_permits_available.clone())).expect("register semaphore_permits_available");
+        registry.register(Box::new(db_pool_size.clone())).expect("register db_pool_size");
+        registry.register(Box::new(db_pool_idle.clone())).expect("register db_pool_idle");
+        registry.register(Box::new(queue_depth.clone())).expect("register jobs_queue_depth");
+
+        Self {
+            registry,
+            jobs_created,
+            jobs_acquired,
+            jobs_succeeded,
+            jobs_failed,
+            jobs_retried,
+            queue_wait_seconds,
+            run_duration_seconds,
+            semaphore_permits_available,
+            db_pool_size,
+            db_pool_idle,
+            queue_depth,
+        }
+    }
+
+    /// Render all registered metrics in the Prometheus text exposition format
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
@@ -12,6 +12,7 @@ use tracing::{error, info};
 /// 3. Signaling workers to stop acquiring new jobs
 /// 4. Waiting for workers to complete current jobs
 /// 5. Closing database connections
+/// 6. Flushing pending trace spans
 pub struct ShutdownCoordinator {
     server_handle: ServerHandle,
     server_task: JoinHandle<Result<(), std::io::Error>>,
@@ -49,6 +50,7 @@ impl ShutdownCoordinator {
     /// 2. Signal workers to stop
     /// 3. Wait for workers to finish current jobs
     /// 4. Close database connections
+    /// 5. Flush pending trace spans
     pub async fn wait_for_shutdown(self) -> Result<(), std::io::Error> {
         // Setup signal handlers
         let ctrl_c = async {
@@ -123,6 +125,9 @@ impl ShutdownCoordinator {
         self.pool.close().await;
         info!("Database connections closed");
 
+        // 6. Flush pending spans to the OTLP collector (no-op when tracing is disabled)
+        crate::telemetry::shutdown();
+
         info!("Graceful shutdown completed successfully");
         Ok(())
     }
@@ -0,0 +1,138 @@
+use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+use opentelemetry::{global, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use std::collections::HashMap;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Service name reported on every exported span
+const SERVICE_NAME: &str = "job-processor";
+
+/// Initialize the OTLP span exporter and W3C trace context propagation
+///
+/// Spans are exported over OTLP/HTTP to `{endpoint}/v1/traces` in batches.
+/// The batch processor runs on its own thread (`TokioCurrentThread`) because
+/// actix runs on a current-thread runtime, which would otherwise deadlock on flush.
+pub fn init_tracer(endpoint: &str) -> Result<Tracer, String> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .map_err(|e| format!("Failed to build OTLP exporter: {}", e))?;
+
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::TokioCurrentThread)
+        .with_resource(Resource::new(vec![KeyValue::new("service.name", SERVICE_NAME)]))
+        .build();
+
+    let tracer = provider.tracer(SERVICE_NAME);
+    global::set_tracer_provider(provider);
+
+    Ok(tracer)
+}
+
+/// Flush pending spans and shut the exporter down
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+/// Capture the current span's trace context as W3C headers (`traceparent`, `tracestate`)
+///
+/// Returns `None` when tracing is disabled or there is no active trace,
+/// so nothing is stored with the job.
+pub fn current_trace_context() -> Option<serde_json::Value> {
+    let context = Span::current().context();
+    if !context.span().span_context().is_valid() {
+        return None;
+    }
+
+    let mut carrier: HashMap<String, String> = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut carrier));
+
+    serde_json::to_value(carrier).ok()
+}
+
+/// Link `span` to the trace stored with a job at enqueue time
+///
+/// A link (rather than a parent) keeps job execution in its own trace while
+/// still pointing back at the request that created the job.
+pub fn link_to_enqueue(span: &Span, trace_context: Option<&serde_json::Value>) {
+    let Some(carrier) = trace_context
+        .and_then(|value| serde_json::from_value::<HashMap<String, String>>(value.clone()).ok())
+    else {
+        return;
+    };
+
+    let context = global::get_text_map_propagator(|propagator| propagator.extract(&carrier));
+    let span_context = context.span().span_context().clone();
+
+    if span_context.is_valid() {
+        span.add_link(span_context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::BoxFuture;
+    use opentelemetry::trace::{SpanContext, TraceResult};
+    use opentelemetry_sdk::export::trace::{ExportResult, SpanData, SpanExporter};
+    use std::sync::{Arc, Mutex};
+    use tracing::info_span;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Collector stand-in that keeps exported spans in memory
+    #[derive(Debug, Clone, Default)]
+    struct InMemoryExporter {
+        spans: Arc<Mutex<Vec<SpanData>>>,
+    }
+
+    impl SpanExporter for InMemoryExporter {
+        fn export(&mut self, batch: Vec<SpanData>) -> BoxFuture<'static, ExportResult> {
+            self.spans.lock().unwrap().extend(batch);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[test]
+    fn job_span_links_to_enqueue_trace() -> TraceResult<()> {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let exporter = InMemoryExporter::default();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let trace_context = info_span!("enqueue").in_scope(current_trace_context);
+            assert!(trace_context.as_ref().is_some_and(|value| value.get("traceparent").is_some()));
+
+            let job = info_span!("job");
+            link_to_enqueue(&job, trace_context.as_ref());
+            drop(job);
+        });
+
+        for result in provider.force_flush() {
+            result?;
+        }
+
+        let spans = exporter.spans.lock().unwrap();
+        let enqueue = spans.iter().find(|span| span.name == "enqueue").expect("enqueue span exported");
+        let job = spans.iter().find(|span| span.name == "job").expect("job span exported");
+
+        // Execution runs in its own trace and links back to the request
+        assert_ne!(job.span_context.trace_id(), enqueue.span_context.trace_id());
+        let linked: Vec<&SpanContext> = job.links.iter().map(|link| &link.span_context).collect();
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].trace_id(), enqueue.span_context.trace_id());
+        assert_eq!(linked[0].span_id(), enqueue.span_context.span_id());
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use futures_util::future::BoxFuture;
+use serde_json::{json, Value};
+use tracing::info;
+
+use super::handler::{JobContext, JobHandler};
+
+/// Fans out one child per item and joins on them
+///
+/// Payload: `{"child": "<name>", "items": [...], "join": "<name>"}`. Each child gets
+/// `{"item": <item>}`; the optional join job gets the child ids.
+pub struct FanOut;
+
+impl JobHandler for FanOut {
+    fn handle<'a>(&'a self, ctx: &'a mut JobContext) -> BoxFuture<'a, Result<(), String>> {
+        Box::pin(async move {
+            let payload = ctx.job().payload.clone().unwrap_or(Value::Null);
+
+            let child = payload
+                .get("child")
+                .and_then(Value::as_str)
+                .ok_or("fan_out payload needs a child job name")?
+                .to_string();
+            let items = payload
+                .get("items")
+                .and_then(Value::as_array)
+                .ok_or("fan_out payload needs an items array")?;
+
+            for item in items {
+                ctx.enqueue(child.as_str(), Some(json!({ "item": item })))?;
+            }
+
+            if let Some(join) = payload.get("join").and_then(Value::as_str) {
+                ctx.join(join, Some(json!({})))?;
+            }
+
+            ctx.set_result(json!({ "children": items.len() }));
+            info!(children = items.len(), "Fanned out job");
+            Ok(())
+        })
+    }
+}
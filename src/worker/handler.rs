@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use futures_util::future::BoxFuture;
+use serde_json::Value;
+
+use crate::db::models::JobRow;
+
+/// A job enqueued by a handler
+#[derive(Debug, Clone)]
+pub struct SpawnedJob {
+    pub name: String,
+    pub payload: Option<Value>,
+}
+
+/// Jobs a handler enqueued while processing its job
+#[derive(Debug, Default)]
+pub struct SpawnedJobs {
+    pub children: Vec<SpawnedJob>,
+    /// Runs once every child has finished
+    pub join: Option<SpawnedJob>,
+}
+
+impl SpawnedJobs {
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty() && self.join.is_none()
+    }
+}
+
+/// What a handler sees of its job, and where it enqueues follow-up work
+///
+/// Enqueued jobs are only written when the handler succeeds, in the same
+/// transaction that marks its job 'success'. A failed handler enqueues nothing.
+pub struct JobContext {
+    job: JobRow,
+    spawned: SpawnedJobs,
+    result: Option<Value>,
+}
+
+impl JobContext {
+    pub fn new(job: JobRow) -> Self {
+        Self { job, spawned: SpawnedJobs::default(), result: None }
+    }
+
+    /// The job being processed
+    pub fn job(&self) -> &JobRow {
+        &self.job
+    }
+
+    /// Enqueue a child job
+    pub fn enqueue(&mut self, name: impl Into<String>, payload: Option<Value>) -> Result<(), String> {
+        let name = validate_name(name.into())?;
+        self.spawned.children.push(SpawnedJob { name, payload });
+        Ok(())
+    }
+
+    /// Enqueue a join job that runs once every child enqueued by this handler has
+    /// succeeded, failed or been cancelled
+    ///
+    /// An object payload gets the child job ids under `children`, so the join step
+    /// can look up their outcomes. Calling this again replaces the join job.
+    pub fn join(&mut self, name: impl Into<String>, payload: Option<Value>) -> Result<(), String> {
+        let name = validate_name(name.into())?;
+        self.spawned.join = Some(SpawnedJob { name, payload });
+        Ok(())
+    }
+
+    /// Store a result with the job; it is sent in the completion callback
+    pub fn set_result(&mut self, result: Value) {
+        self.result = Some(result);
+    }
+
+    /// Jobs and result to write when the handler succeeds
+    pub fn into_output(self) -> (SpawnedJobs, Option<Value>) {
+        (self.spawned, self.result)
+    }
+}
+
+/// Same rule as submitted jobs; `jobs.name` is `VARCHAR(10)`
+fn validate_name(name: String) -> Result<String, String> {
+    if (3..=10).contains(&name.chars().count()) {
+        Ok(name)
+    } else {
+        Err(format!("Job name must be between 3 and 10 characters: {}", name))
+    }
+}
+
+/// Processes jobs of one name
+///
+/// Returning `Err` fails the attempt with that message; the job's retry policy applies.
+pub trait JobHandler: Send + Sync {
+    fn handle<'a>(&'a self, ctx: &'a mut JobContext) -> BoxFuture<'a, Result<(), String>>;
+}
+
+/// Handlers by job name; jobs without one fall back to simulated processing
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, Box<dyn JobHandler>>,
+}
+
+impl HandlerRegistry {
+    pub fn register(mut self, name: &str, handler: impl JobHandler + 'static) -> Self {
+        self.handlers.insert(name.to_string(), Box::new(handler));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn JobHandler> {
+        self.handlers.get(name).map(|handler| handler.as_ref())
+    }
+}
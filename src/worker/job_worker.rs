@@ -1,21 +1,43 @@
+use futures_util::FutureExt;
 use sqlx::{Pool, Postgres};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use tokio::time::{sleep, Duration, Instant};
 use tokio::sync::{Semaphore, watch};
 use rand::Rng;
-use tracing::{error, info, warn};
+use tracing::{error, info, info_span, warn, Instrument};
 
 use crate::db::job_repository::JobRepository;
+use crate::metrics::metrics;
+use crate::telemetry;
+use super::handler::{HandlerRegistry, JobContext, SpawnedJobs};
+use super::registry::WorkerRegistration;
 
 /// Background worker for processing jobs
 pub struct JobWorker {
     pool: Pool<Postgres>,
+    handlers: Arc<HandlerRegistry>,
+    /// Job names this worker acquires; empty means every name
+    queues: Vec<String>,
+    heartbeat_secs: u64,
+    stale_secs: u64,
 }
 
 impl JobWorker {
     /// Create a new JobWorker instance
-    pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
+    ///
+    /// The worker only acquires jobs named in `queues` (every job if empty). It registers
+    /// in `workers` with those queues and heartbeats every `heartbeat_secs`; entries
+    /// without a heartbeat for `stale_secs` are removed.
+    pub fn new(
+        pool: Pool<Postgres>,
+        handlers: Arc<HandlerRegistry>,
+        queues: Vec<String>,
+        heartbeat_secs: u64,
+        stale_secs: u64,
+    ) -> Self {
+        Self { pool, handlers, queues, heartbeat_secs, stale_secs }
     }
 
     /// Run worker with semaphore-based bounded concurrency and graceful shutdown
@@ -24,11 +46,14 @@ impl JobWorker {
     /// - Continuously fetches available jobs using acquire_next_job
     /// - Acquires semaphore permit before spawning job processing task
     /// - Spawns concurrent tasks to process jobs (bounded by semaphore)
-    /// - Each task simulates processing with random delay (1-5 seconds)
-    /// - Randomly determines success/failure (75-80% success rate)
-    /// - Updates job status accordingly
+    /// - Each task runs the handler registered for the job's name; jobs without
+    ///   one simulate processing with a random delay (1-5 seconds) and a
+    ///   75-80% success rate
+    /// - Updates job status accordingly, inserting any jobs the handler enqueued
+    ///   in the same transaction
     /// - Sleeps when no jobs are available
     /// - Exits gracefully when shutdown signal is received
+    /// - Stays registered in `workers` with periodic heartbeats while running
     ///
     /// # Arguments
     /// - `worker_id` - Identifier for this worker instance
@@ -44,69 +69,190 @@ impl JobWorker {
     /// # Graceful Shutdown
     /// - Worker stops acquiring new jobs when shutdown signal is received
     /// - Currently processing jobs complete normally
-    /// - Worker exits cleanly after shutdown
+    /// - Worker deregisters and exits once its jobs have completed
     pub async fn run(&self, worker_id: u32, semaphore: Arc<Semaphore>, shutdown_rx: watch::Receiver<bool>) {
-        info!("Worker {} started with semaphore-based concurrency", worker_id);
+        info!(worker_id, "Worker started with semaphore-based concurrency");
+
+        // Keep processing without a registry entry rather than not at all
+        let registration = match WorkerRegistration::register(self.pool.clone(), worker_id, self.queues.clone()).await {
+            Ok(registration) => Some(Arc::new(registration)),
+            Err(e) => {
+                error!(worker_id, error = ?e, "Failed to register worker; running unregistered");
+                None
+            }
+        };
+        let heartbeat = registration
+            .clone()
+            .map(|registration| tokio::spawn(registration.heartbeat(self.heartbeat_secs, self.stale_secs)));
+        let registry_id = registration.as_ref().map(|registration| registration.id());
 
         loop {
             // Check for shutdown signal
             if *shutdown_rx.borrow() {
-                warn!("Worker {} received shutdown signal, stopping...", worker_id);
+                warn!(worker_id, "Worker received shutdown signal, stopping...");
                 break;
             }
-            match JobRepository::acquire_next_job(&self.pool).await {
-                Ok(Some(job)) => {
-                    info!("Worker {} acquired job: id={}, name={}", worker_id, job.id, job.name);
+            match JobRepository::acquire_next_job(&self.pool, worker_id, registry_id, &self.queues).await {
+                Ok(Some((job, ready_at))) => {
+                    info!(worker_id, job_id = job.id, job_name = %job.name, attempt = job.attempts, "Worker acquired job");
+
+                    // updated_at was just set by the acquire UPDATE; earlier attempts, retry
+                    // backoff and time spent 'blocked' are not queue wait
+                    let queue_wait = (job.updated_at - ready_at).num_milliseconds().max(0) as f64 / 1000.0;
+                    metrics().jobs_acquired.with_label_values(&[job.name.as_str()]).inc();
+                    metrics().queue_wait_seconds.with_label_values(&[job.name.as_str()]).observe(queue_wait);
 
                     // Acquire semaphore permit before spawning task
                     let permit = semaphore.clone().acquire_owned().await;
                     match permit {
                         Ok(permit) => {
-                            info!("Worker {} got semaphore permit for job {}", worker_id, job.id);
+                            info!(worker_id, job_id = job.id, job_name = %job.name, "Worker got semaphore permit");
 
                             let pool = self.pool.clone();
+                            let handlers = self.handlers.clone();
+                            let in_flight = registration.as_ref().map(|registration| registration.track());
                             let job_id = job.id;
                             let job_name = job.name.clone();
 
+                            // Every log line emitted while processing carries the job's fields
+                            // and links back to the request that enqueued it when tracing is enabled
+                            let span = info_span!(
+                                "job",
+                                job_id,
+                                job_name = %job_name,
+                                worker_id,
+                                request_id = job.request_id.as_deref()
+                            );
+                            telemetry::link_to_enqueue(&span, job.trace_context.as_ref());
+
                             // Spawn task to process job concurrently
                             tokio::spawn(async move {
-                                // Random delay 1-5 seconds (simulate processing time)
-                                let delay = rand::thread_rng().gen_range(1..=5);
-                                info!("Processing job {} ({}) for {} seconds", job_id, job_name, delay);
-                                sleep(Duration::from_secs(delay)).await;
-
-                                // Random success/failure (75-80% success rate)
-                                let success_rate = rand::thread_rng().gen_range(0..100);
-                                let status = if success_rate < 77 { "success" } else { "failed" };
-
-                                // Update job status
-                                match JobRepository::update_job_status(&pool, job_id, status).await {
-                                    Ok(_) => info!("Completed job {}: status={}", job_id, status),
-                                    Err(e) => error!("Failed to update job {}: {:?}", job_id, e),
+                                let started = Instant::now();
+
+                                let (mut error, spawned, result) = match handlers.get(&job_name) {
+                                    Some(handler) => {
+                                        info!("Processing job with registered handler");
+                                        let mut ctx = JobContext::new(job);
+                                        // A panicking handler fails the attempt instead of leaving the job in 'processing'
+                                        match AssertUnwindSafe(handler.handle(&mut ctx)).catch_unwind().await {
+                                            Ok(Ok(())) => {
+                                                let (spawned, result) = ctx.into_output();
+                                                (None, spawned, result)
+                                            }
+                                            Ok(Err(e)) => (Some(e), SpawnedJobs::default(), None),
+                                            Err(panic) => {
+                                                let message = panic_message(panic.as_ref());
+                                                error!(panic = %message, "Handler panicked");
+                                                (Some(format!("Handler panicked: {}", message)), SpawnedJobs::default(), None)
+                                            }
+                                        }
+                                    }
+                                    None => (simulate_processing().await, SpawnedJobs::default(), None),
+                                };
+                                let status = if error.is_none() { "success" } else { "failed" };
+
+                                // Update job status; the result and enqueued children commit with the completion
+                                let update = if spawned.is_empty() && result.is_none() {
+                                    JobRepository::update_job_status(&pool, job_id, status, Some(worker_id), error.as_deref())
+                                        .await
+                                        .map_err(|e| e.to_string())
+                                } else {
+                                    match JobRepository::complete_job(&pool, job_id, Some(worker_id), &spawned, result.as_ref()).await {
+                                        Ok(updated) => Ok(updated),
+                                        Err(e) => {
+                                            // Nothing of the completion was written; fail the attempt so retries apply
+                                            warn!(error = %e, "Failed to store handler output; failing the attempt");
+                                            error = Some(format!("Failed to store handler output: {}", e));
+                                            JobRepository::update_job_status(&pool, job_id, "failed", Some(worker_id), error.as_deref())
+                                                .await
+                                                .map_err(|e| e.to_string())
+                                        }
+                                    }
+                                };
+                                match update {
+                                    Ok(updated) => {
+                                        info!(status = %updated.status, error = error.as_deref(), "Completed job");
+
+                                        // Only count outcomes the database actually recorded; a failed
+                                        // attempt put back to 'new' is a retry, not a failure
+                                        let outcome = if error.is_none() {
+                                            &metrics().jobs_succeeded
+                                        } else if updated.status == "new" {
+                                            &metrics().jobs_retried
+                                        } else {
+                                            &metrics().jobs_failed
+                                        };
+                                        outcome.with_label_values(&[job_name.as_str()]).inc();
+                                        metrics()
+                                            .run_duration_seconds
+                                            .with_label_values(&[job_name.as_str()])
+                                            .observe(started.elapsed().as_secs_f64());
+                                    }
+                                    Err(e) => error!(error = %e, "Failed to update job status"),
                                 }
 
                                 // Permit is automatically dropped here, releasing the semaphore
                                 drop(permit);
-                                info!("Released semaphore permit for job {}", job_id);
-                            });
+                                drop(in_flight);
+                                info!("Released semaphore permit");
+                            }.instrument(span));
                         }
                         Err(e) => {
-                            error!("Worker {} failed to acquire semaphore: {:?}", worker_id, e);
+                            error!(worker_id, error = ?e, "Worker failed to acquire semaphore");
                         }
                     }
                 }
                 Ok(None) => {
                     // No jobs available, sleep for a bit before checking again
-                    info!("Worker {} found no jobs available, sleeping...", worker_id);
+                    info!(worker_id, "Worker found no jobs available, sleeping...");
                     sleep(Duration::from_secs(5)).await;
                 }
                 Err(e) => {
-                    error!("Worker {} encountered database error: {:?}", worker_id, e);
+                    error!(worker_id, error = ?e, "Worker encountered database error");
                     sleep(Duration::from_secs(1)).await;
                 }
             }
         }
 
-        info!("Worker {} stopped gracefully", worker_id);
+        if let Some(registration) = registration {
+            // Stay listed (and heartbeating) until this worker's jobs are done
+            while registration.in_flight() > 0 {
+                sleep(Duration::from_millis(250)).await;
+            }
+            if let Some(heartbeat) = heartbeat {
+                heartbeat.abort();
+            }
+            registration.deregister().await;
+        }
+
+        info!(worker_id, "Worker stopped gracefully");
     }
 }
+
+/// Stand-in for jobs without a registered handler
+///
+/// # Returns
+/// The failure reason, or `None` on success
+async fn simulate_processing() -> Option<String> {
+    // Random delay 1-5 seconds (simulate processing time)
+    let delay = rand::thread_rng().gen_range(1..=5);
+    info!(delay_secs = delay, "Processing job");
+    sleep(Duration::from_secs(delay)).await;
+
+    // Random success/failure (75-80% success rate)
+    let success_rate = rand::thread_rng().gen_range(0..100);
+    if success_rate < 77 {
+        None
+    } else {
+        Some("Simulated processing failure".to_string())
+    }
+}
+
+/// The message a panic was raised with, as given to `panic!`
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
@@ -1,3 +1,10 @@
 mod job_worker;
+mod fan_out;
+mod registry;
+pub mod handler;
+pub mod webhook_dispatcher;
 
+pub use fan_out::FanOut;
+pub use handler::HandlerRegistry;
 pub use job_worker::JobWorker;
+pub use webhook_dispatcher::WebhookDispatcher;
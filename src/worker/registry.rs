@@ -0,0 +1,113 @@
+use sqlx::{Pool, Postgres};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::db::worker_repository::WorkerRepository;
+
+/// A worker's entry in the `workers` table
+///
+/// Also counts the worker's in-flight jobs, which heartbeats report as the permits
+/// it holds.
+pub struct WorkerRegistration {
+    pool: Pool<Postgres>,
+    id: Uuid,
+    host: String,
+    pid: i32,
+    worker_id: i32,
+    queues: Vec<String>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+/// Marks one job as in flight until dropped
+pub struct InFlight(Arc<AtomicUsize>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl WorkerRegistration {
+    /// Register worker `worker_id` of this process, serving `queues` (every job name if empty)
+    pub async fn register(pool: Pool<Postgres>, worker_id: u32, queues: Vec<String>) -> Result<Self, sqlx::Error> {
+        let registration = Self {
+            pool,
+            id: Uuid::new_v4(),
+            host: hostname(),
+            pid: std::process::id() as i32,
+            worker_id: worker_id as i32,
+            queues,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+        };
+        registration.insert().await?;
+
+        info!(worker_id, registry_id = %registration.id, "Registered worker");
+        Ok(registration)
+    }
+
+    /// Registry id, stored as `locked_by` on the jobs this worker acquires
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Count a job as in flight until the returned guard is dropped
+    pub fn track(&self) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        InFlight(self.in_flight.clone())
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Send a heartbeat every `interval_secs` and remove workers silent for `stale_secs`
+    ///
+    /// Runs until the task is aborted. Every worker sweeps stale entries, so crashed
+    /// processes disappear as long as any worker is alive. An entry removed while this
+    /// worker was unresponsive is registered again.
+    pub async fn heartbeat(self: Arc<Self>, interval_secs: u64, stale_secs: u64) {
+        loop {
+            sleep(Duration::from_secs(interval_secs)).await;
+
+            match WorkerRepository::heartbeat(&self.pool, self.id, self.in_flight() as i32).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(worker_id = self.worker_id, "Worker entry was removed; registering again");
+                    if let Err(e) = self.insert().await {
+                        error!(worker_id = self.worker_id, error = ?e, "Failed to register worker again");
+                    }
+                }
+                Err(e) => error!(worker_id = self.worker_id, error = ?e, "Failed to send worker heartbeat"),
+            }
+
+            if let Err(e) = WorkerRepository::remove_stale(&self.pool, stale_secs as i64).await {
+                error!(error = ?e, "Failed to remove stale workers");
+            }
+        }
+    }
+
+    /// Remove this worker's entry
+    pub async fn deregister(&self) {
+        match WorkerRepository::deregister(&self.pool, self.id).await {
+            Ok(()) => info!(worker_id = self.worker_id, "Deregistered worker"),
+            Err(e) => error!(worker_id = self.worker_id, error = ?e, "Failed to deregister worker"),
+        }
+    }
+
+    async fn insert(&self) -> Result<(), sqlx::Error> {
+        WorkerRepository::register(&self.pool, self.id, &self.host, self.pid, self.worker_id, &self.queues).await
+    }
+}
+
+/// Host name from `HOSTNAME` or `/etc/hostname`
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
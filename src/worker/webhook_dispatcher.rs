@@ -0,0 +1,411 @@
+use hmac::{Hmac, Mac};
+use ipnet::IpNet;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect;
+use sha2::Sha256;
+use sqlx::{Pool, Postgres};
+use std::error::Error;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use tokio::sync::watch;
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+
+use crate::db::models::WebhookDeliveryRow;
+use crate::db::webhook_repository::WebhookRepository;
+
+/// Deliveries claimed per poll
+const CLAIM_BATCH_SIZE: i64 = 20;
+
+/// Header carrying `sha256=<hex HMAC of "{timestamp}.{body}">`
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+/// Header carrying the Unix timestamp included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp";
+/// Header carrying the delivery id; stays the same across retries
+pub const DELIVERY_ID_HEADER: &str = "X-Webhook-Id";
+
+/// Background dispatcher for completion webhooks
+///
+/// Polls `webhook_deliveries` for due rows, POSTs each payload to its URL and records
+/// the outcome. Failed attempts are retried with exponential backoff up to
+/// `max_attempts`; the table is the delivery log. Independent of job workers, so a
+/// slow receiver never holds a job permit.
+///
+/// Redirects are not followed, and URLs whose host is or resolves to an address in
+/// `deny` fail without a retry, so callbacks cannot reach internal services.
+pub struct WebhookDispatcher {
+    pool: Pool<Postgres>,
+    client: reqwest::Client,
+    deny: Arc<[IpNet]>,
+    max_attempts: i32,
+    backoff_secs: i32,
+    timeout_secs: u64,
+}
+
+impl WebhookDispatcher {
+    /// Create a new WebhookDispatcher instance
+    pub fn new(pool: Pool<Postgres>, max_attempts: i32, backoff_secs: i32, timeout_secs: u64, deny: Vec<IpNet>) -> Self {
+        let deny: Arc<[IpNet]> = deny.into();
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout_secs))
+            .redirect(redirect::Policy::none())
+            .dns_resolver(Arc::new(DenyingResolver { deny: deny.clone() }))
+            .build()
+            .expect("Failed to build webhook HTTP client");
+
+        Self { pool, client, deny, max_attempts, backoff_secs, timeout_secs }
+    }
+
+    /// Deliver due webhooks until the shutdown signal is received
+    ///
+    /// Deliveries in flight at shutdown finish first; a delivery interrupted by a
+    /// crash is retried once its lease expires.
+    pub async fn run(&self, shutdown_rx: watch::Receiver<bool>) {
+        info!("Webhook dispatcher started");
+
+        // Long enough to cover a request that runs into its timeout
+        let lease_secs = (self.timeout_secs + 30) as f64;
+
+        loop {
+            if *shutdown_rx.borrow() {
+                warn!("Webhook dispatcher received shutdown signal, stopping...");
+                break;
+            }
+
+            match WebhookRepository::claim_due(&self.pool, CLAIM_BATCH_SIZE, lease_secs).await {
+                Ok(deliveries) if deliveries.is_empty() => sleep(Duration::from_secs(2)).await,
+                Ok(deliveries) => {
+                    futures_util::future::join_all(deliveries.into_iter().map(|d| self.deliver(d))).await;
+                }
+                Err(e) => {
+                    error!(error = ?e, "Webhook dispatcher encountered database error");
+                    sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+
+        info!("Webhook dispatcher stopped gracefully");
+    }
+
+    /// POST one delivery and record the outcome
+    async fn deliver(&self, delivery: WebhookDeliveryRow) {
+        let body = delivery.payload.to_string();
+        let timestamp = chrono::Utc::now().timestamp().to_string();
+
+        let mut request = self
+            .client
+            .post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(DELIVERY_ID_HEADER, delivery.id.to_string())
+            .header(TIMESTAMP_HEADER, &timestamp);
+
+        // Jobs created without an API key (e.g. by the CLI) are delivered unsigned
+        if let Some(secret) = &delivery.webhook_secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &timestamp, &body)));
+        }
+
+        // Hosts given as IP literals are never resolved, so they are checked here
+        let literal = reqwest::Url::parse(&delivery.url).ok().and_then(|url| {
+            let host = url.host_str()?.trim_start_matches('[').trim_end_matches(']');
+            host.parse::<IpAddr>().ok()
+        });
+
+        // The last element says whether the failure may be retried
+        let outcome = match literal.filter(|ip| is_denied(&self.deny, *ip)) {
+            Some(ip) => Err((None, DeniedDestination(ip).to_string(), false)),
+            None => match request.body(body).send().await {
+                Ok(response) if response.status().is_success() => Ok(response.status().as_u16() as i32),
+                Ok(response) => Err((Some(response.status().as_u16() as i32), format!("HTTP {}", response.status()), true)),
+                Err(e) => match denied_destination(&e) {
+                    Some(denied) => Err((None, denied.to_string(), false)),
+                    None => Err((None, e.to_string(), true)),
+                },
+            },
+        };
+
+        let recorded = match outcome {
+            Ok(status_code) => {
+                info!(delivery_id = delivery.id, job_id = delivery.job_id, status_code, "Webhook delivered");
+                WebhookRepository::mark_delivered(&self.pool, delivery.id, status_code).await
+            }
+            Err((status_code, reason, retryable)) => {
+                let retried = WebhookRepository::mark_failed(
+                    &self.pool,
+                    delivery.id,
+                    status_code,
+                    &reason,
+                    if retryable { self.max_attempts } else { 0 },
+                    self.backoff_secs,
+                )
+                .await;
+                if let Ok(retried) = retried {
+                    warn!(
+                        delivery_id = delivery.id,
+                        job_id = delivery.job_id,
+                        attempt = delivery.attempts,
+                        retried,
+                        "Webhook delivery failed: {}",
+                        reason
+                    );
+                }
+                retried.map(|_| ())
+            }
+        };
+
+        if let Err(e) = recorded {
+            error!(delivery_id = delivery.id, error = ?e, "Failed to record webhook delivery");
+        }
+    }
+}
+
+/// Resolves webhook hosts, refusing names with any address in a denied range
+struct DenyingResolver {
+    deny: Arc<[IpNet]>,
+}
+
+impl Resolve for DenyingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let deny = self.deny.clone();
+        Box::pin(async move {
+            // The port is replaced by the URL's
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| is_denied(&deny, addr.ip())) {
+                return Err(Box::new(DeniedDestination(addr.ip())) as Box<dyn Error + Send + Sync>);
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// A webhook host that is or resolves to a denied address
+#[derive(Debug)]
+struct DeniedDestination(IpAddr);
+
+impl fmt::Display for DeniedDestination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Destination {} is in a denied address range", self.0)
+    }
+}
+
+impl Error for DeniedDestination {}
+
+/// IPv4-mapped IPv6 addresses are checked as the IPv4 address they map to
+fn is_denied(deny: &[IpNet], ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+    deny.iter().any(|net| net.contains(&ip))
+}
+
+/// The resolver's refusal, if that is why the request failed
+fn denied_destination(error: &reqwest::Error) -> Option<&DeniedDestination> {
+    let mut source = error.source();
+    while let Some(error) = source {
+        if let Some(denied) = error.downcast_ref::<DeniedDestination>() {
+            return Some(denied);
+        }
+        source = error.source();
+    }
+    None
+}
+
+/// Hex HMAC-SHA256 of `"{timestamp}.{body}"` keyed by the API key's webhook secret
+pub fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use actix_web::http::StatusCode;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    const SECRET: &str = "5f2b8c1d9e4a7f3b6c0d8e2a4f6b1c9d3e7a5f0b2c4d6e8f1a3b5c7d9e0f2a4b";
+
+    /// Request seen by the test receiver: delivery id, timestamp, signature and body
+    type Received = (String, String, String, String);
+
+    /// Local receiver answering with `statuses` in turn and recording every request
+    fn receiver(statuses: &[u16]) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let statuses = Arc::new(Mutex::new(statuses.iter().copied().collect::<VecDeque<_>>()));
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let log = received.clone();
+        let server = HttpServer::new(move || {
+            let statuses = statuses.clone();
+            let log = log.clone();
+            App::new().default_service(web::to(move |req: HttpRequest, body: web::Bytes| {
+                let statuses = statuses.clone();
+                let log = log.clone();
+                async move {
+                    let header = |name: &str| {
+                        req.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or_default().to_string()
+                    };
+                    log.lock().unwrap().push((
+                        header(DELIVERY_ID_HEADER),
+                        header(TIMESTAMP_HEADER),
+                        header(SIGNATURE_HEADER),
+                        String::from_utf8_lossy(&body).to_string(),
+                    ));
+                    let status = statuses.lock().unwrap().pop_front().unwrap_or(200);
+                    HttpResponse::build(StatusCode::from_u16(status).unwrap()).finish()
+                }
+            }))
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+
+        let url = format!("http://{}/hooks", server.addrs()[0]);
+        tokio::spawn(server.run());
+        (url, received)
+    }
+
+    /// Queue one delivery to `url` for a finished job created with a webhook-enabled key
+    async fn queue_delivery(pool: &Pool<Postgres>, url: &str) -> i64 {
+        let key_id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO api_keys (name, key_hash, key_prefix, webhook_secret)
+            VALUES ('hooks', md5(random()::TEXT) || md5(random()::TEXT), 'jp_test', $1)
+            RETURNING id
+            "#,
+        )
+        .bind(SECRET)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        let job_id: i32 = sqlx::query_scalar(
+            "INSERT INTO jobs (name, status, api_key_id, callback_url) VALUES ('notify', 'success', $1, $2) RETURNING id",
+        )
+        .bind(key_id)
+        .bind(url)
+        .fetch_one(pool)
+        .await
+        .unwrap();
+
+        sqlx::query_scalar("INSERT INTO webhook_deliveries (job_id, url, payload) VALUES ($1, $2, $3) RETURNING id")
+            .bind(job_id)
+            .bind(url)
+            .bind(serde_json::json!({ "id": job_id, "status": "success" }))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    /// Claim due deliveries and deliver them the way `run` does
+    async fn dispatch(dispatcher: &WebhookDispatcher) -> usize {
+        let deliveries = WebhookRepository::claim_due(&dispatcher.pool, CLAIM_BATCH_SIZE, 60.0).await.unwrap();
+        let count = deliveries.len();
+        for delivery in deliveries {
+            dispatcher.deliver(delivery).await;
+        }
+        count
+    }
+
+    #[test]
+    fn sign_matches_known_vector() {
+        assert_eq!(
+            sign("whsec_test", "1700000000", r#"{"id":42,"status":"success"}"#),
+            "fb364bf89e775eb51bcfcd098534d258cd6aece25f7c0dbc77eaa4e0f2acf5fd"
+        );
+        assert_eq!(sign("", "0", ""), "b849d5a581847b281957065739df36df2463d1977ea8d6e1e4e6cf33fadc68c3");
+    }
+
+    #[sqlx::test]
+    async fn failed_delivery_is_retried_after_backoff(pool: Pool<Postgres>) {
+        let (url, received) = receiver(&[500, 200]);
+        let delivery_id = queue_delivery(&pool, &url).await;
+        let dispatcher = WebhookDispatcher::new(pool.clone(), 3, 30, 5, Vec::new());
+
+        assert_eq!(dispatch(&dispatcher).await, 1);
+
+        let (status, attempts, status_code, error, backoff): (String, i32, Option<i32>, Option<String>, f64) =
+            sqlx::query_as(
+                r#"
+                SELECT status, attempts, last_status_code, last_error,
+                       EXTRACT(EPOCH FROM next_attempt_at - NOW())::FLOAT8
+                FROM webhook_deliveries
+                WHERE id = $1
+                "#,
+            )
+            .bind(delivery_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!((status.as_str(), attempts, status_code), ("pending", 1, Some(500)));
+        assert_eq!(error.as_deref(), Some("HTTP 500 Internal Server Error"));
+        assert!((25.0..=30.0).contains(&backoff), "retry scheduled in {}s", backoff);
+
+        // Not due again until the backoff has passed
+        assert_eq!(dispatch(&dispatcher).await, 0);
+
+        sqlx::query("UPDATE webhook_deliveries SET next_attempt_at = NOW() WHERE id = $1")
+            .bind(delivery_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(dispatch(&dispatcher).await, 1);
+
+        let (status, attempts, status_code): (String, i32, Option<i32>) =
+            sqlx::query_as("SELECT status, attempts, last_status_code FROM webhook_deliveries WHERE id = $1")
+                .bind(delivery_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), attempts, status_code), ("delivered", 2, Some(200)));
+
+        // Both attempts carry the same delivery id and a valid signature
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        for (id, timestamp, signature, body) in received.iter() {
+            assert_eq!(id, &delivery_id.to_string());
+            assert_eq!(signature, &format!("sha256={}", sign(SECRET, timestamp, body)));
+        }
+    }
+
+    #[sqlx::test]
+    async fn delivery_fails_after_max_attempts(pool: Pool<Postgres>) {
+        let (url, received) = receiver(&[503]);
+        let delivery_id = queue_delivery(&pool, &url).await;
+        let dispatcher = WebhookDispatcher::new(pool.clone(), 1, 30, 5, Vec::new());
+
+        assert_eq!(dispatch(&dispatcher).await, 1);
+
+        let (status, status_code): (String, Option<i32>) =
+            sqlx::query_as("SELECT status, last_status_code FROM webhook_deliveries WHERE id = $1")
+                .bind(delivery_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), status_code), ("failed", Some(503)));
+        assert_eq!(received.lock().unwrap().len(), 1);
+    }
+    #[sqlx::test]
+    async fn denied_destinations_fail_without_retry(pool: Pool<Postgres>) {
+        let (url, received) = receiver(&[200]);
+        let literal = queue_delivery(&pool, &url).await;
+        let resolved = queue_delivery(&pool, &url.replace("127.0.0.1", "localhost")).await;
+        let deny = crate::config::parse_cidrs("deny", crate::config::DEFAULT_WEBHOOK_DENY_CIDRS).unwrap();
+        let dispatcher = WebhookDispatcher::new(pool.clone(), 3, 30, 5, deny);
+
+        assert_eq!(dispatch(&dispatcher).await, 2);
+
+        for delivery_id in [literal, resolved] {
+            let (status, error): (String, Option<String>) =
+                sqlx::query_as("SELECT status, last_error FROM webhook_deliveries WHERE id = $1")
+                    .bind(delivery_id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(status, "failed");
+            assert!(error.as_deref().unwrap_or_default().contains("denied address range"), "{:?}", error);
+        }
+        assert!(received.lock().unwrap().is_empty());
+    }
+}